@@ -0,0 +1,34 @@
+use crate::domain::ControlCommand;
+use crate::infrastructure;
+use crate::infrastructure::settings::Settings;
+use clap::Subcommand;
+use tracing::{info, warn};
+#[derive(Subcommand)]
+pub enum ControlAction {
+    Start { label: Option<String> },
+    Stop,
+    Mark { note: String },
+    Pause,
+    Resume,
+    Status,
+}
+pub async fn run(action: ControlAction) -> anyhow::Result<()> {
+    let settings = Settings::new_allow_missing_gemini()?;
+    let command = match action {
+        ControlAction::Start { label } => ControlCommand::Start { label },
+        ControlAction::Stop => ControlCommand::Stop,
+        ControlAction::Mark { note } => ControlCommand::Mark { note },
+        ControlAction::Pause => ControlCommand::Pause,
+        ControlAction::Resume => ControlCommand::Resume,
+        ControlAction::Status => ControlCommand::Status,
+    };
+    let reply = infrastructure::control::send(&settings.control_addr, &command).await?;
+    println!("{}", serde_json::to_string_pretty(&reply)?);
+    if reply.ok {
+        info!("Control {:?}: {}", command, reply.message);
+        Ok(())
+    } else {
+        warn!("Control {:?} rejected: {}", command, reply.message);
+        anyhow::bail!(reply.message)
+    }
+}
@@ -1,16 +1,12 @@
 use crate::infrastructure;
 use crate::infrastructure::settings::Settings;
 use crate::use_cases;
-use tracing::info;
-pub async fn run(date: String) {
+use tracing::{error, info};
+pub async fn run(date: String, cache_mode: crate::domain::CacheMode) {
     let settings: Settings = Settings::new().unwrap();
     info!("Evaluating content for: {}", date);
     let prompts = infrastructure::prompts::Prompts::load().unwrap();
-    let gemini = infrastructure::llm::GeminiClient::new(
-        settings.google_api_key.clone(),
-        settings.gemini_model.clone(),
-        prompts,
-    );
+    let gemini = super::llm_client(&settings, cache_mode, prompts);
     let supabase = if !settings.supabase_url.is_empty() {
         Some(infrastructure::api::SupabaseClient::new(
             settings.supabase_url,
@@ -21,5 +17,8 @@ pub async fn run(date: String) {
     };
     let use_case =
         use_cases::evaluate::EvaluateDailyContentUseCase::new(Box::new(gemini), supabase);
-    use_case.execute(&date).await;
+    if let Err(e) = use_case.execute(&date).await {
+        error!("Evaluation failed for {}: {}", date, e);
+        std::process::exit(1);
+    }
 }
@@ -1,3 +1,4 @@
+pub mod control;
 pub mod doctor;
 pub mod evaluate;
 pub mod monitor;
@@ -7,4 +8,37 @@ pub mod record;
 pub mod setup;
 pub mod status;
 pub mod sync;
+pub mod tasks;
 pub mod worker;
+#[derive(clap::Args, Debug, Clone, Copy)]
+pub struct CacheArgs {
+    #[arg(long, conflicts_with = "refresh")]
+    pub no_cache: bool,
+    #[arg(long)]
+    pub refresh: bool,
+}
+impl CacheArgs {
+    pub fn mode(&self) -> crate::domain::CacheMode {
+        match (self.no_cache, self.refresh) {
+            (true, _) => crate::domain::CacheMode::Bypass,
+            (_, true) => crate::domain::CacheMode::Refresh,
+            _ => crate::domain::CacheMode::Use,
+        }
+    }
+}
+pub fn llm_client(
+    settings: &crate::infrastructure::settings::Settings,
+    cache_mode: crate::domain::CacheMode,
+    prompts: crate::infrastructure::prompts::Prompts,
+) -> crate::infrastructure::llm::LlmClient {
+    crate::infrastructure::llm::LlmClient::new(
+        settings.llm.clone(),
+        crate::infrastructure::llm_cache::LlmCache::open(&settings.llm_cache, cache_mode),
+        prompts,
+    )
+    .unwrap_or_else(|e| {
+        tracing::error!("Failed to initialise LLM client: {}", e);
+        eprintln!("Failed to initialise LLM client: {}", e);
+        std::process::exit(1);
+    })
+}
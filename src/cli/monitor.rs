@@ -2,7 +2,7 @@ use crate::infrastructure;
 use crate::infrastructure::settings::Settings;
 use crate::use_cases;
 use std::sync::Arc;
-use tracing::info;
+use tracing::{info, warn};
 pub async fn run(spawn_worker: bool) {
     let settings: Settings = if spawn_worker {
         Settings::new().unwrap()
@@ -12,32 +12,47 @@ pub async fn run(spawn_worker: bool) {
     info!("Starting monitor mode...");
     let recorder = Arc::new(infrastructure::audio::AudioRecorder::new());
     let monitor = Arc::new(tokio::sync::Mutex::new(
-        infrastructure::process::ProcessMonitor::new(settings.process_names.clone()),
-    ));
-    let repo = Arc::new(infrastructure::tasks::TaskRepository::new(
-        crate::domain::constants::TASKS_PATH,
-    ));
-    let watcher = Arc::new(infrastructure::watcher::FileWatcher::new(
-        crate::domain::constants::CLOUD_SYNC_DIR,
+        infrastructure::process::ProcessMonitor::new(
+            settings.profiles.clone(),
+            settings.windows_process_source,
+        ),
     ));
     let prompts = infrastructure::prompts::Prompts::load().unwrap();
-    let (gemini, curator): (
+    let (gemini, curator, daily_pipeline): (
         Arc<dyn crate::domain::ContentGenerator>,
         Arc<dyn crate::domain::Curator>,
+        _,
     ) = if spawn_worker {
-        let client = Arc::new(infrastructure::llm::GeminiClient::new(
-            settings.google_api_key.clone(),
-            settings.gemini_model.clone(),
-            prompts,
-        ));
-        (client.clone(), client)
+        let client = infrastructure::llm::LlmBudget::new(
+            Arc::new(super::llm_client(
+                &settings,
+                crate::domain::CacheMode::Use,
+                prompts,
+            )),
+            Arc::new(tokio::sync::Semaphore::new(
+                settings.task_concurrency.llm_calls,
+            )),
+        );
+        let daily_pipeline = super::worker::daily_pipeline(&settings, client.clone());
+        let client = Arc::new(client);
+        (client.clone(), client, daily_pipeline)
     } else {
-        let noop = Arc::new(infrastructure::llm::NoopGemini::new());
-        (noop.clone(), noop)
+        let noop = infrastructure::llm::NoopGemini::new();
+        let daily_pipeline = super::worker::daily_pipeline(&settings, noop.clone());
+        let noop = Arc::new(noop);
+        (noop.clone(), noop, daily_pipeline)
     };
     let event_repo = Arc::new(
         infrastructure::db::EventRepository::new(&settings.db_path.to_string_lossy()).await,
     );
+    let repo = Arc::new(infrastructure::tasks::TaskRepository::new(
+        event_repo.pool(),
+    ));
+    repo.import_legacy().await;
+    let watcher = Arc::new(infrastructure::watcher::FileWatcher::new(
+        crate::domain::constants::CLOUD_SYNC_DIR,
+        repo.clone(),
+    ));
     let activity_sync = Arc::new(use_cases::sync_activity::ActivitySyncUseCase::new(
         event_repo.clone(),
     ));
@@ -50,6 +65,13 @@ pub async fn run(spawn_worker: bool) {
         "Monitor config: spawn_worker={}, recording_dir={:?}",
         spawn_worker, recording_dir
     );
+    let control_rx = match infrastructure::control::listen(&settings.control_addr).await {
+        Ok(rx) => Some(rx),
+        Err(e) => {
+            warn!("Manual recording control disabled: {}", e);
+            None
+        }
+    };
     let use_case = use_cases::monitor::MonitorUseCase::new(
         recorder,
         monitor,
@@ -59,14 +81,17 @@ pub async fn run(spawn_worker: bool) {
         curator,
         watcher,
         activity_sync,
+        Arc::new(daily_pipeline),
         event_repo,
         settings.check_interval,
         recording_dir,
-        settings.audio_device,
-        settings.silence_threshold,
+        settings.recording_config(),
         settings.start_debounce_secs,
         settings.stop_grace_secs,
         settings.min_recording_secs,
+        settings.task_retry,
+        settings.task_concurrency.clone(),
+        control_rx,
     );
     use_case.execute(spawn_worker).await;
 }
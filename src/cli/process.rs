@@ -3,27 +3,36 @@ use crate::infrastructure;
 use crate::infrastructure::settings::Settings;
 use crate::use_cases;
 use std::sync::Arc;
-use tracing::info;
-pub async fn run(file: String) {
+use tracing::{error, info};
+pub async fn run(file: String, cache_mode: crate::domain::CacheMode) {
     let settings: Settings = Settings::new().unwrap();
     info!("Processing file: {}", file);
     let prompts = infrastructure::prompts::Prompts::load().unwrap();
-    let gemini = Arc::new(infrastructure::llm::GeminiClient::new(
-        settings.google_api_key,
-        settings.gemini_model,
-        prompts,
-    ));
+    let gemini = Arc::new(super::llm_client(&settings, cache_mode, prompts));
     let event_repo = Arc::new(
         infrastructure::db::EventRepository::new(&settings.db_path.to_string_lossy()).await,
     );
     let use_case = use_cases::process::ProcessUseCase::new(gemini.clone(), event_repo, gemini);
-    use_case
+    if let Err(e) = use_case
         .execute_session(&domain::Task {
             id: "manual".to_string(),
             created_at: chrono::Utc::now(),
             status: crate::domain::constants::STATUS_PROCESSING.to_string(),
             task_type: crate::domain::constants::TASK_TYPE_PROCESS_SESSION.to_string(),
-            file_paths: vec![file],
+            file_paths: vec![file.clone()],
+            session_id: None,
+            profile: None,
+            lease_owner: None,
+            lease_expires_at: None,
+            attempts: 0,
+            last_error: None,
+            next_attempt_at: None,
+            depends_on: Vec::new(),
+            date: None,
         })
-        .await;
+        .await
+    {
+        error!("Processing failed for {}: {}", file, e);
+        std::process::exit(1);
+    }
 }
@@ -7,26 +7,26 @@ use tokio::signal;
 use tracing::info;
 
 pub async fn run() -> anyhow::Result<()> {
-    if !cfg!(target_os = "windows") {
-        tracing::warn!("Recording is disabled on non-Windows hosts; skipping.");
+    let settings: AudioRecordingSettings =
+        infrastructure::settings::Settings::get_audio_recording_settings()?;
+    if !settings.audio_backend.available_on_host() {
+        tracing::warn!(
+            "The {:?} capture backend is unavailable on this host; skipping.",
+            settings.audio_backend
+        );
         return Ok(());
     }
     info!("Starting audio recording...");
-    let settings: AudioRecordingSettings =
-        infrastructure::settings::Settings::get_audio_recording_settings()?;
     let recorder = Arc::new(infrastructure::audio::AudioRecorder::new());
     std::fs::create_dir_all(&settings.recording_dir)?;
     let timestamp = Local::now().format("%Y%m%d_%H%M%S").to_string();
-    let file_name = format!("recording_{}.wav", timestamp);
+    let config = settings.recording_config();
+    let file_name = format!("recording_{}.{}", timestamp, config.format.extension());
     let output_path = settings.recording_dir.join(file_name);
     info!("Recording to: {}", output_path.display());
-    recorder.start(
-        output_path.clone(),
-        settings.sample_rate,
-        settings.channels,
-        settings.audio_device,
-        settings.silence_threshold,
-    );
+    recorder
+        .start(output_path.clone(), config)
+        .map_err(|e| anyhow::anyhow!("Failed to start recording: {}", e))?;
     signal::ctrl_c().await?;
     info!("Stopping recording...");
     recorder.stop();
@@ -1,7 +1,11 @@
 use crate::infrastructure::settings::Settings;
 use crate::use_cases;
+use tracing::error;
 pub async fn run() {
     let settings: Settings = Settings::new().unwrap();
     let use_case = use_cases::sync::SyncUseCase::new(settings);
-    use_case.execute().await;
+    if let Err(e) = use_case.execute().await {
+        error!("Sync failed: {}", e);
+        std::process::exit(1);
+    }
 }
@@ -0,0 +1,187 @@
+use crate::domain::Task;
+use crate::infrastructure;
+use crate::infrastructure::settings::Settings;
+use crate::use_cases::tasks::{TaskFilter, TaskQueueUseCase};
+use chrono::{Duration, NaiveDate};
+use clap::Subcommand;
+use std::sync::Arc;
+#[derive(Subcommand)]
+pub enum TasksAction {
+    List {
+        #[arg(long)]
+        status: Option<String>,
+        #[arg(long = "type")]
+        task_type: Option<String>,
+        #[arg(long, value_parser = parse_date)]
+        date: Option<NaiveDate>,
+    },
+    Show {
+        id: String,
+    },
+    Retry {
+        #[arg(required_unless_present = "failed", conflicts_with = "failed")]
+        id: Option<String>,
+        #[arg(long)]
+        failed: bool,
+    },
+    Cancel {
+        id: String,
+    },
+    Enqueue {
+        task_type: String,
+        files: Vec<String>,
+        #[arg(long, value_parser = parse_date)]
+        date: Option<NaiveDate>,
+    },
+    Prune {
+        #[arg(long, value_parser = parse_age)]
+        older_than: Duration,
+    },
+}
+fn parse_date(value: &str) -> Result<NaiveDate, String> {
+    NaiveDate::parse_from_str(value, "%Y%m%d")
+        .or_else(|_| NaiveDate::parse_from_str(value, "%Y-%m-%d"))
+        .map_err(|_| format!("expected YYYYMMDD or YYYY-MM-DD, got {}", value))
+}
+fn parse_age(value: &str) -> Result<Duration, String> {
+    let (amount, unit) = value.split_at(value.len().saturating_sub(1));
+    let amount: i64 = amount
+        .parse()
+        .map_err(|_| format!("expected <number><d|h|m>, got {}", value))?;
+    match unit {
+        "d" => Ok(Duration::days(amount)),
+        "h" => Ok(Duration::hours(amount)),
+        "m" => Ok(Duration::minutes(amount)),
+        _ => Err(format!("expected <number><d|h|m>, got {}", value)),
+    }
+}
+fn print_row(task: &Task) {
+    println!(
+        "{}  {:<10}  {:<15}  {:>2}  {}  {}",
+        task.id,
+        task.status,
+        task.task_type,
+        task.attempts,
+        task.created_at
+            .with_timezone(&chrono::Local)
+            .format("%Y-%m-%d %H:%M:%S"),
+        task.file_paths.join(", ")
+    );
+}
+fn print_detail(task: &Task) {
+    let local = |t: chrono::DateTime<chrono::Utc>| {
+        t.with_timezone(&chrono::Local)
+            .format("%Y-%m-%d %H:%M:%S")
+            .to_string()
+    };
+    println!("id:          {}", task.id);
+    println!("status:      {}", task.status);
+    println!("type:        {}", task.task_type);
+    println!("created:     {}", local(task.created_at));
+    println!("attempts:    {}", task.attempts);
+    if let Some(date) = &task.date {
+        println!("date:        {}", date);
+    }
+    if let Some(session_id) = &task.session_id {
+        println!("session:     {}", session_id);
+    }
+    if let Some(profile) = &task.profile {
+        println!("profile:     {}", profile.name);
+    }
+    if let Some(owner) = &task.lease_owner {
+        println!("lease:       {}", owner);
+    }
+    if let Some(expires) = task.lease_expires_at {
+        println!("lease until: {}", local(expires));
+    }
+    if let Some(next) = task.next_attempt_at {
+        println!("next try:    {}", local(next));
+    }
+    if let Some(error) = &task.last_error {
+        println!("last error:  {}", error);
+    }
+    for dependency in &task.depends_on {
+        println!("depends on:  {}", dependency);
+    }
+    for path in &task.file_paths {
+        println!("file:        {}", path);
+    }
+}
+pub async fn run(action: TasksAction, json: bool) -> anyhow::Result<()> {
+    let settings = Settings::new_allow_missing_gemini()?;
+    let repo = Arc::new(infrastructure::tasks::TaskRepository::new(
+        infrastructure::db::connect(&settings.db_path.to_string_lossy()).await,
+    ));
+    repo.import_legacy().await;
+    let use_case = TaskQueueUseCase::new(repo);
+    match action {
+        TasksAction::List {
+            status,
+            task_type,
+            date,
+        } => {
+            let filter = TaskFilter {
+                status,
+                task_type,
+                date,
+            };
+            let tasks = use_case.list(&filter).await;
+            if json {
+                println!("{}", serde_json::to_string_pretty(&tasks)?);
+            } else {
+                tasks.iter().for_each(print_row);
+                println!("{} task(s)", tasks.len());
+            }
+        }
+        TasksAction::Show { id } => {
+            let task = use_case.show(&id).await?;
+            if json {
+                println!("{}", serde_json::to_string_pretty(&task)?);
+            } else {
+                print_detail(&task);
+            }
+        }
+        TasksAction::Retry { id, failed } => {
+            let tasks = match id {
+                Some(id) if !failed => vec![use_case.retry(&id).await?],
+                _ => use_case.retry_failed().await,
+            };
+            if json {
+                println!("{}", serde_json::to_string_pretty(&tasks)?);
+            } else {
+                tasks.iter().for_each(print_row);
+                println!("{} task(s) requeued", tasks.len());
+            }
+        }
+        TasksAction::Cancel { id } => {
+            let task = use_case.cancel(&id).await?;
+            if json {
+                println!("{}", serde_json::to_string_pretty(&task)?);
+            } else {
+                print_row(&task);
+            }
+        }
+        TasksAction::Enqueue {
+            task_type,
+            files,
+            date,
+        } => {
+            let date = date.map(|d| d.format("%Y%m%d").to_string());
+            let task = use_case.enqueue(&task_type, files, date).await?;
+            if json {
+                println!("{}", serde_json::to_string_pretty(&task)?);
+            } else {
+                print_row(&task);
+            }
+        }
+        TasksAction::Prune { older_than } => {
+            let removed = use_case.prune(older_than).await;
+            if json {
+                println!("{}", serde_json::json!({ "removed": removed }));
+            } else {
+                println!("{} task(s) removed", removed);
+            }
+        }
+    }
+    Ok(())
+}
@@ -1,30 +1,64 @@
-use crate::domain::Environment;
+use crate::domain::{Curator, Environment, Novelizer};
 use crate::infrastructure;
 use crate::infrastructure::settings::Settings;
 use crate::use_cases;
 use std::sync::Arc;
 use tracing::info;
 
+pub fn daily_pipeline<T>(
+    settings: &Settings,
+    llm: T,
+) -> use_cases::daily_pipeline::DailyPipelineUseCase
+where
+    T: Novelizer + Curator + Clone + 'static,
+{
+    let supabase = if !settings.supabase_url.is_empty() {
+        Some(infrastructure::api::SupabaseClient::new(
+            settings.supabase_url.clone(),
+            settings.supabase_service_role_key.clone(),
+        ))
+    } else {
+        None
+    };
+    use_cases::daily_pipeline::DailyPipelineUseCase::new(
+        use_cases::build_novel::BuildNovelUseCase::new(
+            Box::new(llm.clone()),
+            Box::new(llm.clone()),
+            Box::new(infrastructure::PythonImageGenerator::new()),
+        ),
+        use_cases::evaluate::EvaluateDailyContentUseCase::new(Box::new(llm), supabase),
+        use_cases::sync::SyncUseCase::new(settings.clone()),
+    )
+}
+
 pub async fn run() {
     let settings: Settings = Settings::new().unwrap();
     let env = infrastructure::fs_utils::LocalEnvironment;
     env.ensure_directories();
 
-    let repo = Arc::new(infrastructure::tasks::TaskRepository::new(
-        crate::domain::constants::TASKS_PATH,
-    ));
     let prompts = infrastructure::prompts::Prompts::load().unwrap();
-    let gemini = Arc::new(infrastructure::llm::GeminiClient::new(
-        settings.google_api_key.clone(),
-        settings.gemini_model.clone(),
-        prompts,
-    ));
+    let gemini = infrastructure::llm::LlmBudget::new(
+        Arc::new(super::llm_client(
+            &settings,
+            crate::domain::CacheMode::Use,
+            prompts,
+        )),
+        Arc::new(tokio::sync::Semaphore::new(
+            settings.task_concurrency.llm_calls,
+        )),
+    );
     let event_repo = Arc::new(
         infrastructure::db::EventRepository::new(&settings.db_path.to_string_lossy()).await,
     );
+    let repo = Arc::new(infrastructure::tasks::TaskRepository::new(
+        event_repo.pool(),
+    ));
+    repo.import_legacy().await;
     let activity_sync = Arc::new(use_cases::sync_activity::ActivitySyncUseCase::new(
         event_repo.clone(),
     ));
+    let daily_pipeline = Arc::new(daily_pipeline(&settings, gemini.clone()));
+    let gemini = Arc::new(gemini);
 
     let task_runner = use_cases::task_runner::TaskRunner::new(
         gemini.clone(),
@@ -32,6 +66,9 @@ pub async fn run() {
         event_repo,
         gemini.clone(),
         activity_sync,
+        daily_pipeline,
+        settings.task_retry,
+        settings.task_concurrency.clone(),
     );
 
     info!("Starting worker loop (Gemini processing only)...");
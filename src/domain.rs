@@ -8,10 +8,75 @@ pub struct Task {
     pub status: String,
     pub task_type: String,
     pub file_paths: Vec<String>,
+    #[serde(default)]
+    pub session_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub profile: Option<TaskProfile>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lease_owner: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lease_expires_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub attempts: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_error: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub next_attempt_at: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub depends_on: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub date: Option<String>,
+}
+#[derive(Debug, Clone, Copy)]
+pub struct RetryPolicy {
+    pub max_attempts: u32,
+    pub base_delay_secs: u64,
+    pub max_delay_secs: u64,
+}
+impl RetryPolicy {
+    pub fn next_attempt_at(&self, attempts: u32) -> Option<DateTime<Utc>> {
+        if attempts >= self.max_attempts {
+            return None;
+        }
+        let exponent = attempts.saturating_sub(1).min(16);
+        let delay = self
+            .base_delay_secs
+            .saturating_mul(1 << exponent)
+            .min(self.max_delay_secs);
+        Some(Utc::now() + chrono::Duration::seconds(delay as i64))
+    }
+}
+#[derive(Debug, Clone)]
+pub struct TaskConcurrency {
+    pub per_type: std::collections::HashMap<String, usize>,
+    pub llm_calls: usize,
+}
+impl TaskConcurrency {
+    pub fn limit(&self, task_type: &str) -> usize {
+        self.per_type
+            .get(task_type)
+            .copied()
+            .or_else(|| {
+                crate::domain::constants::DEFAULT_TASK_CONCURRENCY
+                    .iter()
+                    .find(|(t, _)| *t == task_type)
+                    .map(|(_, n)| *n)
+            })
+            .unwrap_or(crate::domain::constants::DEFAULT_TASK_TYPE_CONCURRENCY)
+    }
+}
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct TaskProfile {
+    pub name: String,
+    #[serde(default)]
+    pub summary_prompt: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
 }
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub enum SourceType {
     WindowsAudio,
+    AudioDevice,
     WindowsActivity,
     UbuntuMonitor,
     System,
@@ -39,16 +104,113 @@ pub struct Evaluation {
     pub quality_score: u8,
     pub reasoning: String,
 }
+#[derive(Debug, Clone, thiserror::Error)]
+pub enum LlmError {
+    #[error("rate limited: {message}")]
+    RateLimited {
+        message: String,
+        retry_after_secs: Option<u64>,
+    },
+    #[error("quota exhausted: {0}")]
+    Quota(String),
+    #[error("blocked by safety filter: {0}")]
+    Blocked(String),
+    #[error("transport error: {0}")]
+    Transport(String),
+    #[error("malformed response: {0}")]
+    BadFormat(String),
+    #[error("API error (status {status}): {message}")]
+    Api { status: u16, message: String },
+}
+impl LlmError {
+    pub fn is_retryable(&self) -> bool {
+        !matches!(self, LlmError::Blocked(_) | LlmError::Api { .. })
+    }
+    pub fn retry_after_secs(&self) -> Option<u64> {
+        match self {
+            LlmError::RateLimited {
+                retry_after_secs, ..
+            } => *retry_after_secs,
+            _ => None,
+        }
+    }
+}
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum LlmProviderKind {
+    Gemini,
+    Ollama,
+    OpenAi,
+    Whisper,
+}
+impl LlmProviderKind {
+    pub fn can_generate(&self) -> bool {
+        !matches!(self, LlmProviderKind::Whisper)
+    }
+    pub fn can_transcribe(&self) -> bool {
+        matches!(self, LlmProviderKind::Gemini | LlmProviderKind::Whisper)
+    }
+    pub fn default_base_url(&self) -> &'static str {
+        match self {
+            LlmProviderKind::Gemini => crate::domain::constants::DEFAULT_GEMINI_BASE_URL,
+            LlmProviderKind::Ollama => crate::domain::constants::DEFAULT_OLLAMA_BASE_URL,
+            LlmProviderKind::OpenAi | LlmProviderKind::Whisper => "",
+        }
+    }
+}
+#[derive(Debug, Clone)]
+pub struct LlmProvider {
+    pub name: String,
+    pub kind: LlmProviderKind,
+    pub model: String,
+    pub base_url: String,
+    pub api_key: Option<String>,
+    pub timeout_secs: u64,
+    pub requests_per_minute: Option<u32>,
+    pub burst: u32,
+    pub max_retries: u32,
+    pub inline_max_bytes: u64,
+    pub upload_base_url: String,
+}
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CacheMode {
+    Use,
+    Bypass,
+    Refresh,
+}
+#[derive(Debug, Clone)]
+pub struct LlmCacheConfig {
+    pub enabled: bool,
+    pub dir: std::path::PathBuf,
+    pub ttl_secs: u64,
+    pub max_bytes: u64,
+}
+#[derive(Debug, Clone)]
+pub struct LlmRouting {
+    pub transcription: Vec<LlmProvider>,
+    pub summarization: Vec<LlmProvider>,
+    pub novelization: Vec<LlmProvider>,
+    pub evaluation: Vec<LlmProvider>,
+}
 #[async_trait::async_trait]
 pub trait Novelizer: Send + Sync {
-    async fn generate_chapter(&self, summary: &str, context: &str) -> String;
+    async fn generate_chapter(&self, summary: &str, context: &str) -> Result<String, LlmError>;
 }
 #[async_trait::async_trait]
 pub trait Curator: Send + Sync {
-    async fn evaluate(&self, summary: &str, novel: &str) -> Evaluation;
-    async fn verify_summary(&self, summary: &str, transcript: &str, activities: &str)
-        -> Evaluation;
-    async fn summarize_session(&self, transcript: &str, activities: &str) -> String;
+    async fn evaluate(&self, summary: &str, novel: &str) -> Result<Evaluation, LlmError>;
+    async fn verify_summary(
+        &self,
+        summary: &str,
+        transcript: &str,
+        activities: &str,
+    ) -> Result<Evaluation, LlmError>;
+    async fn summarize_session(
+        &self,
+        transcript: &str,
+        activities: &str,
+        variant: Option<&str>,
+    ) -> Result<String, LlmError>;
 }
 #[async_trait::async_trait]
 pub trait ImageGenerator: Send + Sync {
@@ -59,29 +221,294 @@ pub trait Environment: Send + Sync {
     fn ensure_directories(&self);
     fn ensure_config(&self);
 }
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum GateMode {
+    Drop,
+    Mark,
+}
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum AudioFormat {
+    Wav,
+    Flac,
+}
+impl AudioFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            AudioFormat::Wav => "wav",
+            AudioFormat::Flac => "flac",
+        }
+    }
+}
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum CaptureBackend {
+    Device,
+    Pulse,
+    File,
+    Synthetic,
+}
+impl CaptureBackend {
+    pub fn available_on_host(&self) -> bool {
+        match self {
+            CaptureBackend::Device => cfg!(target_os = "windows"),
+            CaptureBackend::Pulse => cfg!(target_os = "linux"),
+            CaptureBackend::File | CaptureBackend::Synthetic => true,
+        }
+    }
+}
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct AudioSource {
+    pub name: String,
+    #[serde(default)]
+    pub device_name: Option<String>,
+    #[serde(default)]
+    pub application: Option<String>,
+}
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum DeviceEventKind {
+    Lost,
+    Reopened,
+    Fallback,
+}
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DeviceEvent {
+    pub timestamp: DateTime<Utc>,
+    pub source: String,
+    pub kind: DeviceEventKind,
+    pub device: Option<String>,
+    pub detail: String,
+}
+#[derive(Debug, Clone)]
+pub struct RecordingConfig {
+    pub sample_rate: u32,
+    pub channels: u16,
+    pub format: AudioFormat,
+    pub sources: Vec<AudioSource>,
+    pub backend: CaptureBackend,
+    pub fixture_path: Option<std::path::PathBuf>,
+    pub synthetic_pattern: String,
+    pub silence_threshold: f32,
+    pub vad_hangover_ms: u64,
+    pub vad_pre_roll_ms: u64,
+    pub vad_mode: GateMode,
+    pub max_segment_secs: u64,
+    pub pre_roll_secs: u64,
+    pub session_id: Option<String>,
+}
 pub trait AudioRecorder: Send + Sync {
-    fn start(
-        &self,
-        output_path: std::path::PathBuf,
-        sample_rate: u32,
-        channels: u16,
-        device_name: Option<String>,
-        silence_threshold: f32,
-    );
+    fn arm(&self, config: RecordingConfig);
+    fn disarm(&self);
+    fn start(&self, output_path: std::path::PathBuf, config: RecordingConfig)
+        -> Result<(), String>;
     fn stop(&self) -> Option<std::path::PathBuf>;
+    fn take_finished_segments(&self) -> Vec<std::path::PathBuf>;
+    fn take_device_events(&self) -> Vec<DeviceEvent>;
+}
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum TriggerRule {
+    Process(String),
+    WindowTitle(String),
+    Cmdline(String),
+    All(Vec<TriggerRule>),
+    Any(Vec<TriggerRule>),
+    Not(Box<TriggerRule>),
+}
+impl TriggerRule {
+    pub fn uses_window_title(&self) -> bool {
+        match self {
+            TriggerRule::WindowTitle(_) => true,
+            TriggerRule::Process(_) | TriggerRule::Cmdline(_) => false,
+            TriggerRule::All(rules) | TriggerRule::Any(rules) => {
+                rules.iter().any(TriggerRule::uses_window_title)
+            }
+            TriggerRule::Not(rule) => rule.uses_window_title(),
+        }
+    }
+    pub fn uses_cmdline(&self) -> bool {
+        match self {
+            TriggerRule::Cmdline(_) => true,
+            TriggerRule::Process(_) | TriggerRule::WindowTitle(_) => false,
+            TriggerRule::All(rules) | TriggerRule::Any(rules) => {
+                rules.iter().any(TriggerRule::uses_cmdline)
+            }
+            TriggerRule::Not(rule) => rule.uses_cmdline(),
+        }
+    }
+}
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct RecordingProfile {
+    pub name: String,
+    #[serde(default)]
+    pub processes: Vec<String>,
+    #[serde(default)]
+    pub rule: Option<TriggerRule>,
+    #[serde(default)]
+    pub sources: Vec<AudioSource>,
+    #[serde(default)]
+    pub silence_threshold: Option<f32>,
+    #[serde(default)]
+    pub start_debounce_secs: Option<u64>,
+    #[serde(default)]
+    pub stop_grace_secs: Option<u64>,
+    #[serde(default)]
+    pub min_recording_secs: Option<u64>,
+    #[serde(default)]
+    pub summary_prompt: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+impl RecordingProfile {
+    pub fn trigger(&self) -> Option<TriggerRule> {
+        let processes = (!self.processes.is_empty()).then(|| {
+            TriggerRule::Any(
+                self.processes
+                    .iter()
+                    .cloned()
+                    .map(TriggerRule::Process)
+                    .collect(),
+            )
+        });
+        match (processes, self.rule.clone()) {
+            (Some(processes), Some(rule)) => Some(TriggerRule::All(vec![processes, rule])),
+            (processes, rule) => processes.or(rule),
+        }
+    }
+    pub fn task_profile(&self) -> TaskProfile {
+        TaskProfile {
+            name: self.name.clone(),
+            summary_prompt: self.summary_prompt.clone(),
+            tags: self.tags.clone(),
+        }
+    }
+    pub fn apply(&self, config: &RecordingConfig) -> RecordingConfig {
+        let mut config = config.clone();
+        if !self.sources.is_empty() {
+            config.sources = self.sources.clone();
+        }
+        if let Some(threshold) = self.silence_threshold {
+            config.silence_threshold = threshold;
+        }
+        config
+    }
+}
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum WindowsProcessSource {
+    Feed,
+    Poll,
+}
+#[derive(Debug, Clone)]
+pub struct ProcessMatch {
+    pub profile: RecordingProfile,
+    pub process: String,
 }
 pub trait ProcessMonitor: Send + Sync {
-    fn is_running(&mut self) -> bool;
+    fn detect(&mut self) -> Option<ProcessMatch>;
+}
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(tag = "command", rename_all = "snake_case")]
+pub enum ControlCommand {
+    Start {
+        #[serde(default)]
+        label: Option<String>,
+    },
+    Stop,
+    Mark {
+        note: String,
+    },
+    Pause,
+    Resume,
+    Status,
+}
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct MonitorStatus {
+    pub recording: bool,
+    pub manual: bool,
+    pub paused: bool,
+    pub session_id: Option<String>,
+    pub profile: Option<String>,
+    pub label: Option<String>,
+    pub elapsed_secs: Option<u64>,
+    pub detected: Option<String>,
+}
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ControlReply {
+    pub ok: bool,
+    pub message: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<MonitorStatus>,
+}
+impl ControlReply {
+    pub fn ok(message: impl Into<String>) -> Self {
+        Self {
+            ok: true,
+            message: message.into(),
+            status: None,
+        }
+    }
+    pub fn error(message: impl Into<String>) -> Self {
+        Self {
+            ok: false,
+            message: message.into(),
+            status: None,
+        }
+    }
 }
+pub struct ControlRequest {
+    pub command: ControlCommand,
+    pub reply: tokio::sync::oneshot::Sender<ControlReply>,
+}
+#[async_trait::async_trait]
 pub trait TaskRepository: Send + Sync {
-    fn add(&self, task_type: &str, file_paths: Vec<String>) -> Task;
-    fn load(&self) -> Vec<Task>;
-    fn update_status(&self, id: &str, status: &str);
+    async fn add(&self, task_type: &str, file_paths: Vec<String>) -> Task;
+    async fn add_with_session(
+        &self,
+        task_type: &str,
+        file_paths: Vec<String>,
+        session_id: Option<String>,
+    ) -> Task;
+    async fn add_with_profile(
+        &self,
+        task_type: &str,
+        file_paths: Vec<String>,
+        session_id: Option<String>,
+        profile: Option<TaskProfile>,
+    ) -> Task;
+    async fn add_task(&self, task: Task) -> bool;
+    async fn load(&self) -> Vec<Task>;
+    async fn get(&self, id: &str) -> Option<Task>;
+    async fn update_status(&self, id: &str, status: &str, worker_id: Option<&str>) -> bool;
+    async fn claim(
+        &self,
+        worker_id: &str,
+        lease_secs: u64,
+        exclude_types: &[String],
+    ) -> Option<Task>;
+    async fn heartbeat(&self, id: &str, worker_id: &str, lease_secs: u64) -> bool;
+    async fn expired(&self) -> Vec<Task>;
+    async fn reclaim(&self, id: &str, error: &str, retry_at: Option<DateTime<Utc>>)
+        -> Option<Task>;
+    async fn record_failure(
+        &self,
+        id: &str,
+        worker_id: &str,
+        error: &str,
+        retry_at: Option<DateTime<Utc>>,
+    ) -> bool;
+    async fn requeue(&self, id: &str) -> bool;
+    async fn update_dependencies(&self, id: &str, depends_on: &[String]) -> bool;
+    async fn cascade(&self, id: &str, from: &str, to: &str, reason: Option<&str>) -> Vec<Task>;
+    async fn prune(&self, before: DateTime<Utc>) -> u64;
+    async fn wait_for_task(&self, timeout: std::time::Duration);
 }
 #[async_trait::async_trait]
 pub trait ContentGenerator: Send + Sync {
-    async fn generate_content(&self, prompt: &str) -> String;
-    async fn transcribe(&self, file_path: &str) -> String;
+    async fn generate_content(&self, prompt: &str) -> Result<String, LlmError>;
+    async fn transcribe(&self, file_path: &str) -> Result<String, LlmError>;
 }
 pub trait FileWatcher: Send + Sync {
     fn start(&self);
@@ -21,6 +21,9 @@ pub const FFMPEG_CMD: &str = "ffmpeg";
 pub const PYTHON_CMD: &str = "python";
 pub const UV_CMD: &str = "uv";
 pub const SQLITE_CMD: &str = "sqlite3";
+pub const WMCTRL_CMD: &str = "wmctrl";
+pub const PAREC_CMD: &str = "parec";
+pub const PACTL_CMD: &str = "pactl";
 #[cfg(windows)]
 pub const POWERSHELL_PATH: &str = "powershell.exe";
 #[cfg(not(windows))]
@@ -30,11 +33,62 @@ pub const STATUS_PENDING: &str = "pending";
 pub const STATUS_PROCESSING: &str = "processing";
 pub const STATUS_COMPLETED: &str = "completed";
 pub const STATUS_FAILED: &str = "failed";
+pub const STATUS_CANCELLED: &str = "cancelled";
 pub const TASK_TYPE_PROCESS_SESSION: &str = "process_session";
 pub const TASK_TYPE_SYNC_ACTIVITY: &str = "sync_activity";
+pub const TASK_TYPE_BUILD_NOVEL: &str = "build_novel";
+pub const TASK_TYPE_EVALUATE: &str = "evaluate";
+pub const TASK_TYPE_GENERATE_IMAGE: &str = "generate_image";
+pub const TASK_TYPE_SYNC: &str = "sync";
+pub const TASK_TYPES: &[&str] = &[
+    TASK_TYPE_PROCESS_SESSION,
+    TASK_TYPE_SYNC_ACTIVITY,
+    TASK_TYPE_BUILD_NOVEL,
+    TASK_TYPE_EVALUATE,
+    TASK_TYPE_GENERATE_IMAGE,
+    TASK_TYPE_SYNC,
+];
+pub const DAILY_TASK_TYPES: &[&str] = &[
+    TASK_TYPE_BUILD_NOVEL,
+    TASK_TYPE_EVALUATE,
+    TASK_TYPE_GENERATE_IMAGE,
+    TASK_TYPE_SYNC,
+];
 pub const SQL_INSERT_EVENT: &str =
     "INSERT INTO life_events (id, timestamp, source_type, metadata) VALUES (?, ?, ?, ?)";
 pub const SQL_QUERY_EVENTS: &str = "SELECT id, timestamp, source_type, metadata FROM life_events WHERE timestamp >= ? AND timestamp <= ? ORDER BY timestamp ASC";
+pub const SQL_TASK_COLUMN_MIGRATIONS: &[(&str, &str)] = &[
+    (
+        "attempts",
+        "ALTER TABLE tasks ADD COLUMN attempts INTEGER NOT NULL DEFAULT 0",
+    ),
+    ("last_error", "ALTER TABLE tasks ADD COLUMN last_error TEXT"),
+    (
+        "next_attempt_at",
+        "ALTER TABLE tasks ADD COLUMN next_attempt_at DATETIME",
+    ),
+    (
+        "depends_on",
+        "ALTER TABLE tasks ADD COLUMN depends_on TEXT NOT NULL DEFAULT '[]'",
+    ),
+    ("date", "ALTER TABLE tasks ADD COLUMN date TEXT"),
+];
+pub const SQL_INSERT_TASK: &str = "INSERT OR IGNORE INTO tasks (id, created_at, status, task_type, file_paths, session_id, profile, lease_owner, lease_expires_at, attempts, last_error, next_attempt_at, depends_on, date) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)";
+pub const SQL_QUERY_TASKS: &str = "SELECT id, created_at, status, task_type, file_paths, session_id, profile, lease_owner, lease_expires_at, attempts, last_error, next_attempt_at, depends_on, date FROM tasks ORDER BY created_at ASC";
+pub const SQL_UPDATE_TASK_STATUS: &str = "UPDATE tasks SET status = ?, lease_owner = NULL, lease_expires_at = NULL WHERE id = ? AND lease_owner IS ?";
+pub const SQL_GET_TASK: &str = "SELECT id, created_at, status, task_type, file_paths, session_id, profile, lease_owner, lease_expires_at, attempts, last_error, next_attempt_at, depends_on, date FROM tasks WHERE id = ?";
+pub const SQL_REQUEUE_TASK: &str = "UPDATE tasks SET status = ?, attempts = 0, next_attempt_at = NULL, lease_owner = NULL, lease_expires_at = NULL WHERE id = ?";
+pub const SQL_PRUNE_TASKS: &str = "DELETE FROM tasks WHERE status IN (?, ?, ?) AND created_at < ?";
+pub const SQL_CLAIM_TASK: &str = "UPDATE tasks SET status = ?, lease_owner = ?, lease_expires_at = ?, attempts = attempts + 1, next_attempt_at = NULL WHERE id = (SELECT t.id FROM tasks t WHERE t.status = ? AND (t.next_attempt_at IS NULL OR t.next_attempt_at <= ?) AND t.task_type NOT IN (SELECT value FROM json_each(?)) AND NOT EXISTS (SELECT 1 FROM json_each(t.depends_on) d JOIN tasks upstream ON upstream.id = d.value WHERE upstream.status != ?) ORDER BY t.created_at ASC LIMIT 1) RETURNING id, created_at, status, task_type, file_paths, session_id, profile, lease_owner, lease_expires_at, attempts, last_error, next_attempt_at, depends_on, date";
+pub const SQL_FAIL_TASK: &str = "UPDATE tasks SET status = ?, last_error = ?, next_attempt_at = ?, lease_owner = NULL, lease_expires_at = NULL WHERE id = ? AND status = ? AND lease_owner = ?";
+pub const SQL_HEARTBEAT_TASK: &str =
+    "UPDATE tasks SET lease_expires_at = ? WHERE id = ? AND lease_owner = ? AND status = ?";
+pub const SQL_EXPIRED_TASKS: &str = "SELECT id, created_at, status, task_type, file_paths, session_id, profile, lease_owner, lease_expires_at, attempts, last_error, next_attempt_at, depends_on, date FROM tasks WHERE status = ? AND (lease_expires_at IS NULL OR lease_expires_at < ?)";
+pub const SQL_RECLAIM_TASK: &str = "UPDATE tasks SET status = ?, last_error = ?, next_attempt_at = ?, lease_owner = NULL, lease_expires_at = NULL WHERE id = ? AND status = ? AND (lease_expires_at IS NULL OR lease_expires_at < ?) RETURNING id, created_at, status, task_type, file_paths, session_id, profile, lease_owner, lease_expires_at, attempts, last_error, next_attempt_at, depends_on, date";
+pub const SQL_DATA_VERSION: &str = "PRAGMA data_version";
+pub const SQL_UPDATE_TASK_DEPENDENCIES: &str =
+    "UPDATE tasks SET depends_on = ? WHERE id = ? AND status = ?";
+pub const SQL_CASCADE_TASKS: &str = "WITH RECURSIVE dependents(id) AS (SELECT ? UNION SELECT t.id FROM tasks t, json_each(t.depends_on) d JOIN dependents ON d.value = dependents.id) UPDATE tasks SET status = ?, last_error = ?, next_attempt_at = NULL, lease_owner = NULL, lease_expires_at = NULL WHERE id IN (SELECT id FROM dependents) AND id != ? AND status = ? RETURNING id, created_at, status, task_type, file_paths, session_id, profile, lease_owner, lease_expires_at, attempts, last_error, next_attempt_at, depends_on, date";
 pub const SUMMARY_FILE_TEMPLATE: &str = "data/summaries/{}_summary.txt";
 pub const NOVEL_FILE_TEMPLATE: &str = "data/novels/{}.md";
 pub const PHOTO_FILE_TEMPLATE: &str = "data/photos/{}.png";
@@ -42,16 +96,48 @@ pub const EVALUATION_FILE_TEMPLATE: &str = "data/evaluations/{}.json";
 pub const CONFIG_DEFAULT_JSON: &str = "[]";
 pub const TASKS_FILE_NAME: &str = "tasks.json";
 pub const DEFAULT_PROCESS_NAMES: &str = "VRChat,Discord";
+pub const DEFAULT_WINDOWS_PROCESS_SOURCE: &str = "feed";
+pub const WINDOWS_FEED_INTERVAL_MS: u64 = 1000;
+pub const WINDOWS_FEED_RETRY_SECS: u64 = 30;
+pub const MANUAL_PROFILE_NAME: &str = "manual";
+pub const DEFAULT_CONTROL_ADDR: &str = "127.0.0.1:47820";
+pub const CONTROL_QUEUE_DEPTH: usize = 16;
+pub const CONTROL_MAX_LINE_BYTES: usize = 64 * 1024;
+pub const DEFAULT_PROFILE_NAME: &str = "default";
 pub const DEFAULT_DB_PATH: &str = "data/vlog.db";
 pub const DEFAULT_SILENCE_THRESHOLD: f64 = 0.02;
+pub const DEFAULT_VAD_HANGOVER_MS: u64 = 800;
+pub const DEFAULT_VAD_PRE_ROLL_MS: u64 = 300;
+pub const DEFAULT_VAD_MODE: &str = "drop";
+pub const DEFAULT_PRE_ROLL_SECS: u64 = 0;
+pub const DEFAULT_MAX_DURATION_MINUTES: u64 = 30;
+pub const VAD_FRAME_MS: u32 = 20;
+pub const SEGMENTS_SIDECAR_EXTENSION: &str = "segments.json";
 pub const BYTES_PER_SECOND_16K_MONO: f64 = 16000.0 * 2.0;
 pub const DEFAULT_SAMPLE_RATE: u32 = 48000;
 pub const DEFAULT_CHANNELS: u16 = 2;
 pub const TARGET_SAMPLE_RATE: u32 = 16000;
 pub const TARGET_CHANNELS: u16 = 1;
 pub const OPUS_BITRATE: u32 = 12000;
+pub const RESAMPLER_ZERO_CROSSINGS: u32 = 8;
+pub const TRANSCRIBE_CHUNK_FRAMES: usize = 4096;
 pub const DEFAULT_BITS_PER_SAMPLE: u16 = 16;
 pub const WAV_PART_EXTENSION: &str = "wav.part";
+pub const FLAC_PART_EXTENSION: &str = "flac.part";
+pub const DEFAULT_AUDIO_FORMAT: &str = "wav";
+pub const FLAC_BLOCK_SIZE: usize = 4096;
+pub const CORRUPT_RECORDINGS_DIR: &str = "corrupt";
+pub const DEFAULT_AUDIO_SOURCE_NAME: &str = "mic";
+pub const DEFAULT_CAPTURE_BACKEND: &str = "device";
+pub const DEFAULT_SYNTHETIC_PATTERN: &str = "tone";
+pub const SYNTHETIC_TONE_HZ: f32 = 440.0;
+pub const SYNTHETIC_AMPLITUDE: f32 = 0.3;
+pub const PULSE_DEFAULT_MONITOR: &str = "@DEFAULT_MONITOR@";
+pub const PULSE_CLIENT_NAME: &str = "vlog";
+pub const PULSE_READ_BYTES: usize = 4096;
+pub const MAX_SOURCE_SKEW_MS: u64 = 500;
+pub const DEVICE_STALL_MS: u64 = 2000;
+pub const DEVICE_RETRY_MS: u64 = 1000;
 pub const AUDIO_LOG_INTERVAL_SECS: u64 = 10;
 pub const AUDIO_SLEEP_MS: u64 = 100;
 pub const MONITOR_CHECK_INTERVAL_DEFAULT: u64 = 5;
@@ -59,6 +145,20 @@ pub const START_DEBOUNCE_SECS_DEFAULT: u64 = 2;
 pub const STOP_GRACE_SECS_DEFAULT: u64 = 10;
 pub const MIN_RECORDING_SECS_DEFAULT: u64 = 60;
 pub const TASK_LOOP_INTERVAL_SECS: u64 = 30;
+pub const TASK_WAKE_POLL_MS: u64 = 1000;
+pub const DAILY_PIPELINE_DELAY_SECS: i64 = 3600;
+pub const TASK_LEASE_SECS: u64 = 300;
+pub const TASK_HEARTBEAT_SECS: u64 = 60;
+pub const DEFAULT_TASK_MAX_ATTEMPTS: u32 = 5;
+pub const DEFAULT_TASK_RETRY_BASE_SECS: u64 = 60;
+pub const DEFAULT_TASK_RETRY_MAX_SECS: u64 = 3600;
+pub const DEFAULT_TASK_CONCURRENCY: &[(&str, usize)] =
+    &[(TASK_TYPE_PROCESS_SESSION, 2), (TASK_TYPE_SYNC_ACTIVITY, 4)];
+pub const DEFAULT_TASK_TYPE_CONCURRENCY: usize = 1;
+pub const DEFAULT_LLM_CONCURRENCY: usize = 2;
+pub const TASK_LEASE_EXPIRED_ERROR: &str =
+    "lease expired while processing (worker exited or stalled)";
+pub const TASKS_IMPORTED_SUFFIX: &str = "imported";
 pub const HEALTH_CHECK_INTERVAL_SECS: u64 = 30;
 pub const HEALTH_THRESHOLD_PERCENT: f64 = 90.0;
 pub const WATCHER_POLL_INTERVAL_SECS: u64 = 2;
@@ -103,3 +203,24 @@ pub const TRANSCRIPT_FILLERS: &[&str] = &[
 ];
 pub const PROHIBITED_WORDS: &[&str] = &["hmd", "controller", "virtual", "vr"];
 pub const DEFAULT_GEMINI_MODEL: &str = "gemini-3-flash-preview";
+pub const DEFAULT_GEMINI_BASE_URL: &str = "https://generativelanguage.googleapis.com/v1beta";
+pub const DEFAULT_GEMINI_API_KEY_ENV: &str = "GOOGLE_API_KEY";
+pub const DEFAULT_OLLAMA_BASE_URL: &str = "http://127.0.0.1:11434";
+pub const DEFAULT_LLM_TIMEOUT_SECS: u64 = 300;
+pub const DEFAULT_LLM_MAX_RETRIES: u32 = 2;
+pub const DEFAULT_LLM_BURST: u32 = 1;
+pub const LLM_RETRY_BASE_MS: u64 = 1000;
+pub const LLM_RETRY_MAX_MS: u64 = 30_000;
+pub const DEFAULT_GEMINI_INLINE_MAX_MB: u64 = 15;
+pub const GEMINI_UPLOAD_CHUNK_BYTES: usize = 8 * 1024 * 1024;
+pub const GEMINI_UPLOAD_MAX_RESUMES: u32 = 5;
+pub const GEMINI_FILE_POLL_SECS: u64 = 2;
+pub const GEMINI_FILE_ACTIVE_TIMEOUT_SECS: u64 = 300;
+pub const DEFAULT_LLM_CACHE_DIR: &str = "data/cache/llm";
+pub const DEFAULT_LLM_CACHE_TTL_DAYS: u64 = 30;
+pub const DEFAULT_LLM_CACHE_MAX_SIZE_MB: u64 = 512;
+pub const LEGACY_OLLAMA_MODEL_PREFIX: &str = "ollama:";
+pub const LLM_PROVIDER_GEMINI: &str = "gemini";
+pub const LLM_PROVIDER_OLLAMA: &str = "ollama";
+pub const LLM_PROVIDER_WHISPER: &str = "whisper";
+pub const WHISPER_CMD: &str = "whisper";
@@ -1,14 +1,18 @@
 pub mod api;
 pub mod audio;
+pub mod control;
 pub mod db;
 pub mod fs_utils;
 pub mod llm;
+pub mod llm_cache;
+pub mod llm_middleware;
 pub mod preprocessor;
 pub mod process;
 pub mod prompts;
 pub mod settings;
 pub mod tasks;
 pub mod watcher;
+pub mod windows_feed;
 use crate::domain::ImageGenerator;
 use std::process::Command;
 pub struct PythonImageGenerator;
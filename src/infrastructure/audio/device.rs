@@ -0,0 +1,154 @@
+use super::mixer::SourceBuffer;
+use crate::domain::AudioSource;
+use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+use std::sync::{Arc, Mutex};
+use tracing::{error, info, warn};
+pub struct SelectedInput {
+    pub device: cpal::Device,
+    pub device_label: String,
+    pub config: cpal::StreamConfig,
+    pub sample_format: cpal::SampleFormat,
+    pub loopback: bool,
+    pub fell_back: bool,
+}
+fn find_device(
+    host: &cpal::Host,
+    source: &AudioSource,
+    allow_fallback: bool,
+) -> anyhow::Result<(cpal::Device, bool, bool)> {
+    let default_input = || {
+        host.default_input_device()
+            .ok_or_else(|| anyhow::anyhow!("No default input device for source '{}'", source.name))
+    };
+    let Some(name) = source.device_name.as_ref() else {
+        return Ok((default_input()?, false, false));
+    };
+    let matches = |d: &cpal::Device| d.name().map(|n| n.contains(name)).unwrap_or(false);
+    if let Some(device) = host.input_devices()?.find(matches) {
+        return Ok((device, false, false));
+    }
+    if let Some(device) = host.output_devices()?.find(matches) {
+        return Ok((device, true, false));
+    }
+    if allow_fallback {
+        warn!(
+            "Audio device '{}' for source '{}' not found; falling back to the default input",
+            name, source.name
+        );
+        return Ok((default_input()?, false, true));
+    }
+    Err(anyhow::anyhow!(
+        "Audio device '{}' for source '{}' not found",
+        name,
+        source.name
+    ))
+}
+pub fn select_input(
+    host: &cpal::Host,
+    source: &AudioSource,
+    sample_rate: u32,
+    preferred_channels: Option<u16>,
+    allow_fallback: bool,
+) -> anyhow::Result<SelectedInput> {
+    let (device, loopback, fell_back) = find_device(host, source, allow_fallback)?;
+    let ranges: Vec<cpal::SupportedStreamConfigRange> = if loopback {
+        device.supported_output_configs()?.collect()
+    } else {
+        device.supported_input_configs()?.collect()
+    };
+    let candidates: Vec<&cpal::SupportedStreamConfigRange> = ranges
+        .iter()
+        .filter(|c| {
+            c.sample_format() == cpal::SampleFormat::F32
+                || c.sample_format() == cpal::SampleFormat::I16
+        })
+        .filter(|c| c.min_sample_rate().0 <= sample_rate && c.max_sample_rate().0 >= sample_rate)
+        .collect();
+    let range = candidates
+        .iter()
+        .find(|c| Some(c.channels()) == preferred_channels)
+        .or_else(|| candidates.first())
+        .ok_or_else(|| {
+            anyhow::anyhow!(
+                "Source '{}' does not support {} Hz capture",
+                source.name,
+                sample_rate
+            )
+        })?;
+    let sample_format = range.sample_format();
+    let config: cpal::StreamConfig = range
+        .with_sample_rate(cpal::SampleRate(sample_rate))
+        .config();
+    let device_label = device.name().unwrap_or_default();
+    info!(
+        "Opening audio source '{}' on {:?} ({} ch{})",
+        source.name,
+        device_label,
+        config.channels,
+        if loopback { ", loopback" } else { "" }
+    );
+    Ok(SelectedInput {
+        device,
+        device_label,
+        config,
+        sample_format,
+        loopback,
+        fell_back,
+    })
+}
+pub fn open_stream(
+    input: &SelectedInput,
+    buffer: Arc<Mutex<SourceBuffer>>,
+    out_channels: usize,
+    source_name: &str,
+) -> anyhow::Result<cpal::Stream> {
+    let stream = match input.sample_format {
+        cpal::SampleFormat::F32 => {
+            build_stream::<f32>(input, buffer, out_channels, source_name, |s| s)?
+        }
+        cpal::SampleFormat::I16 => {
+            build_stream::<i16>(input, buffer, out_channels, source_name, |s| {
+                s as f32 / i16::MAX as f32
+            })?
+        }
+        sample_format => {
+            error!("Unsupported sample format: {:?}", sample_format);
+            return Err(anyhow::anyhow!(
+                "Unsupported sample format: {:?}",
+                sample_format
+            ));
+        }
+    };
+    stream.play()?;
+    Ok(stream)
+}
+fn build_stream<T>(
+    input: &SelectedInput,
+    buffer: Arc<Mutex<SourceBuffer>>,
+    out_channels: usize,
+    source_name: &str,
+    convert: fn(T) -> f32,
+) -> anyhow::Result<cpal::Stream>
+where
+    T: cpal::SizedSample + 'static,
+{
+    let name = source_name.to_string();
+    let device_channels = input.config.channels as usize;
+    let error_buffer = buffer.clone();
+    let stream = input.device.build_input_stream(
+        &input.config,
+        move |data: &[T], _: &cpal::InputCallbackInfo| {
+            let samples: Vec<f32> = data.iter().map(|&s| convert(s)).collect();
+            buffer
+                .lock()
+                .unwrap()
+                .push(&samples, device_channels, out_channels);
+        },
+        move |err| {
+            error!("Audio stream error on '{}': {}", name, err);
+            error_buffer.lock().unwrap().mark_failed();
+        },
+        None,
+    )?;
+    Ok(stream)
+}
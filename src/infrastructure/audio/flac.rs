@@ -0,0 +1,98 @@
+use flacenc::component::{BitRepr, Stream, StreamInfo};
+use flacenc::error::Verify;
+use flacenc::source::{Context, Fill, FrameBuf};
+use std::fs::File;
+use std::io::{BufWriter, Seek, SeekFrom, Write};
+use std::path::Path;
+pub struct FlacWriter {
+    file: BufWriter<File>,
+    config: flacenc::error::Verified<flacenc::config::Encoder>,
+    stream_info: StreamInfo,
+    framebuf: (FrameBuf, Context),
+    pending: Vec<i32>,
+    channels: usize,
+    block_size: usize,
+}
+impl FlacWriter {
+    pub fn create(path: &Path, sample_rate: u32, channels: u16) -> anyhow::Result<Self> {
+        let channels = channels.max(1) as usize;
+        let block_size = crate::domain::constants::FLAC_BLOCK_SIZE;
+        let bits_per_sample = crate::domain::constants::DEFAULT_BITS_PER_SAMPLE as usize;
+        let config = flacenc::config::Encoder::default()
+            .into_verified()
+            .map_err(|(_, e)| anyhow::anyhow!("Invalid FLAC encoder config: {}", e))?;
+        let mut stream_info = StreamInfo::new(sample_rate as usize, channels, bits_per_sample)
+            .map_err(|e| anyhow::anyhow!("Invalid FLAC stream parameters: {}", e))?;
+        stream_info
+            .set_block_sizes(block_size, block_size)
+            .map_err(|e| anyhow::anyhow!("Invalid FLAC block size: {}", e))?;
+        let mut writer = Self {
+            file: BufWriter::new(File::create(path)?),
+            config,
+            stream_info,
+            framebuf: (
+                FrameBuf::with_size(channels, block_size)
+                    .map_err(|e| anyhow::anyhow!("Invalid FLAC frame buffer: {}", e))?,
+                Context::new(bits_per_sample, channels),
+            ),
+            pending: Vec::new(),
+            channels,
+            block_size,
+        };
+        writer.write_header()?;
+        Ok(writer)
+    }
+    pub fn write_samples(&mut self, samples: &[i16]) -> anyhow::Result<()> {
+        self.pending.extend(samples.iter().map(|&s| s as i32));
+        let block_len = self.block_size * self.channels;
+        while self.pending.len() >= block_len {
+            let block: Vec<i32> = self.pending.drain(..block_len).collect();
+            self.encode_block(&block)?;
+        }
+        Ok(())
+    }
+    pub fn finalize(mut self) -> anyhow::Result<()> {
+        let whole = self.pending.len() - self.pending.len() % self.channels;
+        if whole > 0 {
+            let block: Vec<i32> = self.pending.drain(..whole).collect();
+            self.encode_block(&block)?;
+        }
+        let digest = self.framebuf.1.md5_digest();
+        self.stream_info.set_md5_digest(&digest);
+        self.file.flush()?;
+        self.file.seek(SeekFrom::Start(0))?;
+        self.write_header()?;
+        self.file.flush()?;
+        self.file.get_ref().sync_all()?;
+        Ok(())
+    }
+    fn write_header(&mut self) -> anyhow::Result<()> {
+        let header = Stream::with_stream_info(self.stream_info.clone());
+        let mut sink = flacenc::bitsink::ByteSink::new();
+        header
+            .write(&mut sink)
+            .map_err(|e| anyhow::anyhow!("Failed to serialize FLAC header: {}", e))?;
+        self.file.write_all(sink.as_slice())?;
+        Ok(())
+    }
+    fn encode_block(&mut self, block: &[i32]) -> anyhow::Result<()> {
+        self.framebuf
+            .fill_interleaved(block)
+            .map_err(|e| anyhow::anyhow!("Failed to buffer FLAC samples: {}", e))?;
+        let frame_number = self.framebuf.1.current_frame_number().unwrap_or(0);
+        let frame = flacenc::encode_fixed_size_frame(
+            &self.config,
+            &self.framebuf.0,
+            frame_number,
+            &self.stream_info,
+        )
+        .map_err(|e| anyhow::anyhow!("Failed to encode FLAC frame: {}", e))?;
+        self.stream_info.update_frame_info(&frame);
+        let mut sink = flacenc::bitsink::ByteSink::new();
+        frame
+            .write(&mut sink)
+            .map_err(|e| anyhow::anyhow!("Failed to serialize FLAC frame: {}", e))?;
+        self.file.write_all(sink.as_slice())?;
+        Ok(())
+    }
+}
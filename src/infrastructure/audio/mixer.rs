@@ -0,0 +1,157 @@
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+use tracing::warn;
+pub struct SourceBuffer {
+    samples: VecDeque<f32>,
+    peak: f32,
+    failed: bool,
+    last_push: Instant,
+}
+impl Default for SourceBuffer {
+    fn default() -> Self {
+        Self {
+            samples: VecDeque::new(),
+            peak: 0.0,
+            failed: false,
+            last_push: Instant::now(),
+        }
+    }
+}
+impl SourceBuffer {
+    pub fn push(&mut self, samples: &[f32], device_channels: usize, out_channels: usize) {
+        let device_channels = device_channels.max(1);
+        for frame in samples.chunks_exact(device_channels) {
+            for sample in frame {
+                let abs_sample = sample.abs();
+                if abs_sample > self.peak {
+                    self.peak = abs_sample;
+                }
+            }
+            if out_channels == 1 {
+                self.samples
+                    .push_back(frame.iter().sum::<f32>() / device_channels as f32);
+            } else if out_channels == device_channels {
+                self.samples.extend(frame.iter().copied());
+            } else {
+                self.samples
+                    .extend((0..out_channels).map(|c| frame[c % device_channels]));
+            }
+        }
+        self.last_push = Instant::now();
+    }
+    pub fn mark_failed(&mut self) {
+        self.failed = true;
+    }
+}
+pub struct SourceMixer {
+    names: Vec<String>,
+    buffers: Vec<Arc<Mutex<SourceBuffer>>>,
+    channels: Vec<usize>,
+    downmix: bool,
+    max_skew_frames: usize,
+}
+impl SourceMixer {
+    pub fn new(sample_rate: u32, downmix: bool) -> Self {
+        Self {
+            names: Vec::new(),
+            buffers: Vec::new(),
+            channels: Vec::new(),
+            downmix,
+            max_skew_frames: (sample_rate as u64 * crate::domain::constants::MAX_SOURCE_SKEW_MS
+                / 1000) as usize,
+        }
+    }
+    pub fn add_source(&mut self, name: &str, device_channels: u16) -> usize {
+        let channels = if self.downmix {
+            1
+        } else {
+            device_channels.max(1) as usize
+        };
+        self.names.push(name.to_string());
+        self.buffers
+            .push(Arc::new(Mutex::new(SourceBuffer::default())));
+        self.channels.push(channels);
+        self.buffers.len() - 1
+    }
+    pub fn buffer(&self, index: usize) -> Arc<Mutex<SourceBuffer>> {
+        self.buffers[index].clone()
+    }
+    pub fn source_channels(&self, index: usize) -> usize {
+        self.channels[index]
+    }
+    pub fn reset_health(&self, index: usize) {
+        let mut guard = self.buffers[index].lock().unwrap();
+        guard.failed = false;
+        guard.last_push = Instant::now();
+    }
+    pub fn health(&self, index: usize) -> (bool, Instant) {
+        let guard = self.buffers[index].lock().unwrap();
+        (guard.failed, guard.last_push)
+    }
+    pub fn pad_silence(&self, index: usize, frames: usize) {
+        self.buffers[index]
+            .lock()
+            .unwrap()
+            .samples
+            .extend(std::iter::repeat_n(0.0, frames * self.channels[index]));
+    }
+    pub fn output_channels(&self) -> u16 {
+        self.channels.iter().sum::<usize>() as u16
+    }
+    pub fn channel_names(&self) -> Vec<String> {
+        if self.downmix {
+            self.names.clone()
+        } else {
+            Vec::new()
+        }
+    }
+    pub fn take_peaks(&self) -> Vec<(String, f32)> {
+        self.names
+            .iter()
+            .zip(&self.buffers)
+            .map(|(name, buffer)| {
+                let mut guard = buffer.lock().unwrap();
+                let peak = guard.peak;
+                guard.peak = 0.0;
+                (name.clone(), peak)
+            })
+            .collect()
+    }
+    pub fn pull(&self, flush: bool) -> Vec<f32> {
+        let mut guards: Vec<_> = self.buffers.iter().map(|b| b.lock().unwrap()).collect();
+        let available: Vec<usize> = guards
+            .iter()
+            .zip(&self.channels)
+            .map(|(g, ch)| g.samples.len() / ch)
+            .collect();
+        let leader = available.iter().copied().max().unwrap_or(0);
+        for (i, guard) in guards.iter_mut().enumerate() {
+            let lag = leader - available[i];
+            if lag > 0 && (flush || lag > self.max_skew_frames) {
+                if !flush {
+                    warn!(
+                        "Audio source '{}' is {} frames behind; padding with silence",
+                        self.names[i], lag
+                    );
+                }
+                guard
+                    .samples
+                    .extend(std::iter::repeat_n(0.0, lag * self.channels[i]));
+            }
+        }
+        let frames = guards
+            .iter()
+            .zip(&self.channels)
+            .map(|(g, ch)| g.samples.len() / ch)
+            .min()
+            .unwrap_or(0);
+        let mut out: Vec<f32> = Vec::with_capacity(frames * self.output_channels() as usize);
+        for _ in 0..frames {
+            for (guard, &ch) in guards.iter_mut().zip(&self.channels) {
+                out.extend(guard.samples.drain(..ch));
+            }
+        }
+        out
+    }
+}
@@ -1,6 +1,18 @@
+pub mod device;
+pub mod flac;
+pub mod mixer;
 pub mod normalizer;
+pub mod pulse;
 pub mod recorder;
+pub mod repair;
+pub mod resample;
+pub mod simulated;
 pub mod utils;
+pub mod vad;
+pub mod writer;
 pub use normalizer::normalize_audio;
 pub use recorder::AudioRecorder;
+pub use repair::{repair_recording, RepairOutcome};
+pub use resample::{prepare_for_transcription, Resampler};
 pub use utils::list_devices;
+pub use vad::{DeviceGap, SegmentSidecar, SpeechSegment, VoiceGate};
@@ -0,0 +1,206 @@
+use super::mixer::SourceBuffer;
+use crate::domain::AudioSource;
+use std::io::Read;
+use std::process::{Child, Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use tracing::{error, info, warn};
+enum PulseTarget {
+    Source(String),
+    SinkInput(u32),
+}
+pub struct PulseInput {
+    target: PulseTarget,
+    pub label: String,
+}
+fn pactl(args: &[&str]) -> anyhow::Result<String> {
+    let output = Command::new(crate::domain::constants::PACTL_CMD)
+        .args(args)
+        .output()
+        .map_err(|e| anyhow::anyhow!("Failed to run pactl: {}", e))?;
+    if !output.status.success() {
+        anyhow::bail!(
+            "pactl {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+fn find_sink_input(application: &str) -> anyhow::Result<Option<(u32, String)>> {
+    let listing = pactl(&["list", "sink-inputs"])?;
+    let needle = application.to_lowercase();
+    let mut current: Option<u32> = None;
+    for line in listing.lines() {
+        let line = line.trim();
+        if let Some(index) = line.strip_prefix("Sink Input #") {
+            current = index.trim().parse().ok();
+            continue;
+        }
+        let Some((key, value)) = line.split_once(" = ") else {
+            continue;
+        };
+        if !matches!(
+            key,
+            "application.name" | "application.process.binary" | "media.name"
+        ) {
+            continue;
+        }
+        let value = value.trim_matches('"');
+        if let Some(index) = current {
+            if value.to_lowercase().contains(&needle) {
+                return Ok(Some((index, value.to_string())));
+            }
+        }
+    }
+    Ok(None)
+}
+fn find_source(name: &str) -> anyhow::Result<Option<String>> {
+    let listing = pactl(&["list", "short", "sources"])?;
+    let sources: Vec<&str> = listing
+        .lines()
+        .filter_map(|line| line.split('\t').nth(1))
+        .collect();
+    Ok(sources
+        .iter()
+        .find(|s| **s == name)
+        .or_else(|| sources.iter().find(|s| s.contains(name)))
+        .map(|s| s.to_string()))
+}
+impl PulseInput {
+    pub fn is_app_stream(&self) -> bool {
+        matches!(self.target, PulseTarget::SinkInput(_))
+    }
+}
+pub fn select_pulse_input(source: &AudioSource) -> anyhow::Result<PulseInput> {
+    if let Some(application) = source.application.as_ref() {
+        let (index, matched) = find_sink_input(application)?.ok_or_else(|| {
+            anyhow::anyhow!(
+                "No playback stream matching '{}' for source '{}'",
+                application,
+                source.name
+            )
+        })?;
+        return Ok(PulseInput {
+            target: PulseTarget::SinkInput(index),
+            label: format!("sink-input #{} ({})", index, matched),
+        });
+    }
+    let name = match source.device_name.as_ref() {
+        Some(name) => find_source(name)?.ok_or_else(|| {
+            anyhow::anyhow!(
+                "Pulse source '{}' for source '{}' not found",
+                name,
+                source.name
+            )
+        })?,
+        None => crate::domain::constants::PULSE_DEFAULT_MONITOR.to_string(),
+    };
+    Ok(PulseInput {
+        label: name.clone(),
+        target: PulseTarget::Source(name),
+    })
+}
+pub struct PulseStream {
+    child: Child,
+    closing: Arc<AtomicBool>,
+    reader: Option<JoinHandle<()>>,
+}
+impl PulseStream {
+    pub fn open(
+        input: &PulseInput,
+        sample_rate: u32,
+        channels: u16,
+        buffer: Arc<Mutex<SourceBuffer>>,
+        out_channels: usize,
+        source_name: &str,
+    ) -> anyhow::Result<Self> {
+        let mut command = Command::new(crate::domain::constants::PAREC_CMD);
+        command
+            .arg("--raw")
+            .arg("--format=float32le")
+            .arg(format!("--rate={}", sample_rate))
+            .arg(format!("--channels={}", channels))
+            .arg(format!(
+                "--client-name={}",
+                crate::domain::constants::PULSE_CLIENT_NAME
+            ))
+            .arg(format!("--stream-name={}", source_name));
+        match &input.target {
+            PulseTarget::Source(name) => command.arg(format!("--device={}", name)),
+            PulseTarget::SinkInput(index) => command.arg(format!("--monitor-stream={}", index)),
+        };
+        let mut child = command
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .map_err(|e| anyhow::anyhow!("Failed to start parec: {}", e))?;
+        let mut stdout = child
+            .stdout
+            .take()
+            .ok_or_else(|| anyhow::anyhow!("parec produced no stdout"))?;
+        info!(
+            "Opening audio source '{}' on Pulse {} ({} ch)",
+            source_name, input.label, channels
+        );
+        let name = source_name.to_string();
+        let device_channels = channels as usize;
+        let closing = Arc::new(AtomicBool::new(false));
+        let flag = closing.clone();
+        let reader = std::thread::spawn(move || {
+            let mut bytes = vec![0u8; crate::domain::constants::PULSE_READ_BYTES];
+            let mut pending: Vec<u8> = Vec::new();
+            let mut samples: Vec<f32> = Vec::new();
+            loop {
+                match stdout.read(&mut bytes) {
+                    Ok(0) => {
+                        if !flag.load(Ordering::SeqCst) {
+                            warn!("Pulse capture for '{}' ended", name);
+                        }
+                        break;
+                    }
+                    Ok(n) => {
+                        pending.extend_from_slice(&bytes[..n]);
+                        let frame_bytes = 4 * device_channels;
+                        let usable = pending.len() - pending.len() % frame_bytes;
+                        samples.clear();
+                        samples.extend(
+                            pending[..usable]
+                                .chunks_exact(4)
+                                .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]])),
+                        );
+                        pending.drain(..usable);
+                        buffer
+                            .lock()
+                            .unwrap()
+                            .push(&samples, device_channels, out_channels);
+                    }
+                    Err(_) if flag.load(Ordering::SeqCst) => break,
+                    Err(e) => {
+                        error!("Pulse capture error on '{}': {}", name, e);
+                        break;
+                    }
+                }
+            }
+            if !flag.load(Ordering::SeqCst) {
+                buffer.lock().unwrap().mark_failed();
+            }
+        });
+        Ok(Self {
+            child,
+            closing,
+            reader: Some(reader),
+        })
+    }
+}
+impl Drop for PulseStream {
+    fn drop(&mut self) {
+        self.closing.store(true, Ordering::SeqCst);
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+        if let Some(reader) = self.reader.take() {
+            let _ = reader.join();
+        }
+    }
+}
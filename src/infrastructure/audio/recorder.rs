@@ -1,16 +1,478 @@
+use super::device::{open_stream, select_input};
+use super::mixer::SourceMixer;
+use super::pulse::{select_pulse_input, PulseStream};
+use super::simulated::{Feeder, SimulatedInput};
+use super::vad::{DeviceGap, SegmentSidecar, VoiceGate};
+use super::writer::{RetiredPart, SegmentWriter};
 use crate::domain::AudioRecorder as AudioRecorderTrait;
-use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+use crate::domain::{AudioSource, CaptureBackend, DeviceEvent, DeviceEventKind, RecordingConfig};
+use std::collections::VecDeque;
 use std::path::PathBuf;
 use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc;
 use std::sync::{Arc, Mutex};
 use std::thread;
 use std::thread::JoinHandle;
 use std::time::{Duration, Instant};
-use tracing::{error, info};
+use tracing::{error, info, warn};
+enum CaptureCommand {
+    Start(PathBuf, RecordingConfig, mpsc::Sender<Result<(), String>>),
+    Stop(mpsc::Sender<Option<PathBuf>>),
+    Shutdown,
+}
+struct Session {
+    recording: RecordingConfig,
+    started_at: chrono::DateTime<chrono::Utc>,
+    gate: VoiceGate,
+    writer: SegmentWriter,
+    gaps: Vec<DeviceGap>,
+}
+struct LostSource {
+    padded_until: Instant,
+    retry_at: Instant,
+    gap: Option<usize>,
+}
+enum SourceStream {
+    Device { _stream: cpal::Stream },
+    Pulse { _stream: PulseStream },
+    Simulated { _feeder: Feeder },
+}
+struct SourceLink {
+    source: AudioSource,
+    may_idle: bool,
+    stream: Option<SourceStream>,
+    lost: Option<LostSource>,
+}
+struct Capture {
+    host: cpal::Host,
+    backend: CaptureBackend,
+    mixer: SourceMixer,
+    links: Vec<SourceLink>,
+    preferred_channels: Option<u16>,
+    allow_fallback: bool,
+    spec: hound::WavSpec,
+    channel_names: Vec<String>,
+    ring: VecDeque<f32>,
+    ring_capacity: usize,
+    session: Option<Session>,
+    finished_segments: Arc<Mutex<Vec<PathBuf>>>,
+    device_events: Arc<Mutex<Vec<DeviceEvent>>>,
+    last_log: Instant,
+}
+impl Capture {
+    fn open(
+        config: &RecordingConfig,
+        pre_roll_secs: u64,
+        finished_segments: Arc<Mutex<Vec<PathBuf>>>,
+        device_events: Arc<Mutex<Vec<DeviceEvent>>>,
+    ) -> anyhow::Result<Self> {
+        let sample_rate = config.sample_rate;
+        let host: cpal::Host = cpal::default_host();
+        let downmix = config.sources.len() > 1;
+        let preferred_channels = if downmix { None } else { Some(config.channels) };
+        let allow_fallback = !downmix;
+        let mut mixer = SourceMixer::new(sample_rate, downmix);
+        let mut links: Vec<SourceLink> = Vec::new();
+        for source in &config.sources {
+            let (may_idle, stream) = match config.backend {
+                CaptureBackend::Device => {
+                    let input = select_input(
+                        &host,
+                        source,
+                        sample_rate,
+                        preferred_channels,
+                        allow_fallback,
+                    )?;
+                    let index = mixer.add_source(&source.name, input.config.channels);
+                    let stream = open_stream(
+                        &input,
+                        mixer.buffer(index),
+                        mixer.source_channels(index),
+                        &source.name,
+                    )?;
+                    (input.loopback, SourceStream::Device { _stream: stream })
+                }
+                CaptureBackend::Pulse => {
+                    let input = select_pulse_input(source)?;
+                    let channels = preferred_channels.unwrap_or(1);
+                    let index = mixer.add_source(&source.name, channels);
+                    let stream = PulseStream::open(
+                        &input,
+                        sample_rate,
+                        channels,
+                        mixer.buffer(index),
+                        mixer.source_channels(index),
+                        &source.name,
+                    )?;
+                    (
+                        input.is_app_stream(),
+                        SourceStream::Pulse { _stream: stream },
+                    )
+                }
+                CaptureBackend::File | CaptureBackend::Synthetic => {
+                    let input = Self::simulated_input(config, preferred_channels)?;
+                    info!(
+                        "Opening simulated audio source '{}' ({:?} backend, {} ch)",
+                        source.name, config.backend, input.channels
+                    );
+                    let index = mixer.add_source(&source.name, input.channels);
+                    let feeder = Feeder::start(
+                        input,
+                        sample_rate,
+                        mixer.buffer(index),
+                        mixer.source_channels(index),
+                    );
+                    (false, SourceStream::Simulated { _feeder: feeder })
+                }
+            };
+            links.push(SourceLink {
+                source: source.clone(),
+                may_idle,
+                stream: Some(stream),
+                lost: None,
+            });
+        }
+        let spec: hound::WavSpec = hound::WavSpec {
+            channels: mixer.output_channels(),
+            sample_rate,
+            bits_per_sample: 16,
+            sample_format: hound::SampleFormat::Int,
+        };
+        let channel_names = mixer.channel_names();
+        if !channel_names.is_empty() {
+            info!("Recording channel layout: {}", channel_names.join(", "));
+        }
+        Ok(Self {
+            host,
+            backend: config.backend,
+            mixer,
+            links,
+            preferred_channels,
+            allow_fallback,
+            spec,
+            channel_names,
+            ring: VecDeque::new(),
+            ring_capacity: (pre_roll_secs * sample_rate as u64 * spec.channels as u64) as usize,
+            session: None,
+            finished_segments,
+            device_events,
+            last_log: Instant::now(),
+        })
+    }
+    fn simulated_input(
+        config: &RecordingConfig,
+        preferred_channels: Option<u16>,
+    ) -> anyhow::Result<SimulatedInput> {
+        match config.backend {
+            CaptureBackend::File => {
+                let path = config.fixture_path.as_ref().ok_or_else(|| {
+                    anyhow::anyhow!("audio.fixture_path must be set for the file backend")
+                })?;
+                SimulatedInput::fixture(path, config.sample_rate)
+            }
+            _ => SimulatedInput::synthetic(
+                &config.synthetic_pattern,
+                preferred_channels.unwrap_or(1),
+                config.sample_rate,
+            ),
+        }
+    }
+    fn begin(&mut self, output_path: PathBuf, recording: RecordingConfig) -> anyhow::Result<()> {
+        if let Some(parent) = output_path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| {
+                anyhow::anyhow!("Failed to create recording directory {:?}: {}", parent, e)
+            })?;
+        }
+        let mut gate = VoiceGate::new(
+            self.spec.sample_rate,
+            self.spec.channels,
+            recording.silence_threshold,
+            recording.vad_hangover_ms,
+            recording.vad_pre_roll_ms,
+            recording.vad_mode,
+        );
+        let mut writer = SegmentWriter::create(
+            output_path,
+            recording.format,
+            self.spec,
+            recording.max_segment_secs,
+        )?;
+        let buffered: Vec<f32> = self.ring.drain(..).collect();
+        let buffered_ms = (buffered.len() / self.spec.channels.max(1) as usize) as u64 * 1000
+            / self.spec.sample_rate.max(1) as u64;
+        if buffered_ms > 0 {
+            info!(
+                "Flushing {}ms of pre-roll into the new recording",
+                buffered_ms
+            );
+        }
+        let started_at = chrono::Utc::now() - chrono::Duration::milliseconds(buffered_ms as i64);
+        let mut kept: Vec<f32> = Vec::new();
+        gate.push(&buffered, &mut kept);
+        writer.write(&kept)?;
+        self.session = Some(Session {
+            recording,
+            started_at,
+            gate,
+            writer,
+            gaps: Vec::new(),
+        });
+        for link in self.links.iter_mut() {
+            if let Some(lost) = link.lost.as_mut() {
+                lost.gap = None;
+            }
+        }
+        for index in 0..self.links.len() {
+            if self.links[index].lost.is_some() {
+                self.open_gap(index);
+            }
+        }
+        Ok(())
+    }
+    fn pump(&mut self, flush: bool) {
+        self.watch_sources();
+        let mixed = self.mixer.pull(flush);
+        match self.session.as_mut() {
+            Some(session) => {
+                if !mixed.is_empty() {
+                    let mut kept: Vec<f32> = Vec::new();
+                    session.gate.push(&mixed, &mut kept);
+                    if let Err(e) = session.writer.write(&kept) {
+                        error!("Failed to write audio samples: {}", e);
+                    }
+                }
+                let parts = session.writer.take_retired();
+                for (part, sidecar) in
+                    AudioRecorder::describe_parts(session, parts, &self.channel_names)
+                {
+                    if let Some(path) = AudioRecorder::commit_part(part, sidecar) {
+                        self.finished_segments.lock().unwrap().push(path);
+                    }
+                }
+            }
+            None => {
+                self.ring.extend(mixed);
+                let excess = self.ring.len().saturating_sub(self.ring_capacity);
+                self.ring.drain(..excess);
+            }
+        }
+        if self.last_log.elapsed()
+            >= Duration::from_secs(crate::domain::constants::AUDIO_LOG_INTERVAL_SECS)
+        {
+            let peaks: Vec<String> = self
+                .mixer
+                .take_peaks()
+                .iter()
+                .map(|(name, peak)| format!("{}={:.4}", name, peak))
+                .collect();
+            if self.session.is_some() {
+                info!("Recording status: peak_amplitude {}", peaks.join(" "));
+            }
+            self.last_log = Instant::now();
+        }
+    }
+    fn watch_sources(&mut self) {
+        if !matches!(self.backend, CaptureBackend::Device | CaptureBackend::Pulse) {
+            return;
+        }
+        let stall = Duration::from_millis(crate::domain::constants::DEVICE_STALL_MS);
+        for index in 0..self.links.len() {
+            if self.links[index].lost.is_none() {
+                let (failed, last_push) = self.mixer.health(index);
+                let stalled = !self.links[index].may_idle && last_push.elapsed() >= stall;
+                if failed || stalled {
+                    self.mark_lost(index, last_push, failed);
+                }
+                continue;
+            }
+            self.pad_lost(index);
+            let due = self.links[index]
+                .lost
+                .as_ref()
+                .is_some_and(|lost| Instant::now() >= lost.retry_at);
+            if due {
+                self.reopen(index);
+            }
+        }
+    }
+    fn mark_lost(&mut self, index: usize, last_push: Instant, failed: bool) {
+        let name = self.links[index].source.name.clone();
+        let detail = if failed {
+            "stream reported an error".to_string()
+        } else {
+            format!("no samples for {}ms", last_push.elapsed().as_millis())
+        };
+        warn!("Audio source '{}' lost: {}", name, detail);
+        self.links[index].stream = None;
+        self.links[index].lost = Some(LostSource {
+            padded_until: last_push,
+            retry_at: Instant::now(),
+            gap: None,
+        });
+        self.open_gap(index);
+        self.record_event(&name, DeviceEventKind::Lost, None, detail);
+    }
+    fn pad_lost(&mut self, index: usize) {
+        let sample_rate = self.spec.sample_rate as u64;
+        let Some(lost) = self.links[index].lost.as_mut() else {
+            return;
+        };
+        let frames = lost.padded_until.elapsed().as_millis() as u64 * sample_rate / 1000;
+        if frames == 0 {
+            return;
+        }
+        lost.padded_until += Duration::from_millis(frames * 1000 / sample_rate.max(1));
+        let gap = lost.gap;
+        self.mixer.pad_silence(index, frames as usize);
+        if let (Some(session), Some(gap)) = (self.session.as_mut(), gap) {
+            session.gaps[gap].source_end_ms = Self::session_ms(session);
+        }
+    }
+    fn reopen(&mut self, index: usize) {
+        let source = self.links[index].source.clone();
+        let opened = match self.backend {
+            CaptureBackend::Pulse => select_pulse_input(&source).and_then(|input| {
+                self.mixer.reset_health(index);
+                let channels = self.mixer.source_channels(index);
+                let stream = PulseStream::open(
+                    &input,
+                    self.spec.sample_rate,
+                    self.preferred_channels.unwrap_or(1),
+                    self.mixer.buffer(index),
+                    channels,
+                    &source.name,
+                )?;
+                Ok((
+                    input.label.clone(),
+                    input.is_app_stream(),
+                    false,
+                    SourceStream::Pulse { _stream: stream },
+                ))
+            }),
+            _ => select_input(
+                &self.host,
+                &source,
+                self.spec.sample_rate,
+                self.preferred_channels,
+                self.allow_fallback,
+            )
+            .and_then(|input| {
+                self.mixer.reset_health(index);
+                let stream = open_stream(
+                    &input,
+                    self.mixer.buffer(index),
+                    self.mixer.source_channels(index),
+                    &source.name,
+                )?;
+                Ok((
+                    input.device_label,
+                    input.loopback,
+                    input.fell_back,
+                    SourceStream::Device { _stream: stream },
+                ))
+            }),
+        };
+        match opened {
+            Ok((device_label, may_idle, fell_back, stream)) => {
+                let lost = self.links[index].lost.take();
+                self.links[index].may_idle = may_idle;
+                self.links[index].stream = Some(stream);
+                if let (Some(session), Some(gap)) =
+                    (self.session.as_mut(), lost.and_then(|l| l.gap))
+                {
+                    session.gaps[gap].source_end_ms = Self::session_ms(session);
+                    session.gaps[gap].reopened_on = Some(device_label.clone());
+                }
+                let kind = if fell_back {
+                    DeviceEventKind::Fallback
+                } else {
+                    DeviceEventKind::Reopened
+                };
+                info!(
+                    "Audio source '{}' reopened on {:?}",
+                    source.name, device_label
+                );
+                self.record_event(&source.name, kind, Some(device_label), String::new());
+            }
+            Err(e) => {
+                if let Some(lost) = self.links[index].lost.as_mut() {
+                    lost.retry_at = Instant::now()
+                        + Duration::from_millis(crate::domain::constants::DEVICE_RETRY_MS);
+                }
+                tracing::debug!("Audio source '{}' still unavailable: {}", source.name, e);
+            }
+        }
+    }
+    fn open_gap(&mut self, index: usize) {
+        let Some(session) = self.session.as_mut() else {
+            return;
+        };
+        let now_ms = Self::session_ms(session);
+        session.gaps.push(DeviceGap {
+            source: self.links[index].source.name.clone(),
+            source_start_ms: now_ms,
+            source_end_ms: now_ms,
+            reopened_on: None,
+        });
+        if let Some(lost) = self.links[index].lost.as_mut() {
+            lost.gap = Some(session.gaps.len() - 1);
+        }
+    }
+    fn session_ms(session: &Session) -> u64 {
+        session.gate.frames_to_ms(session.gate.source_frames())
+    }
+    fn record_event(
+        &self,
+        source: &str,
+        kind: DeviceEventKind,
+        device: Option<String>,
+        detail: String,
+    ) {
+        self.device_events.lock().unwrap().push(DeviceEvent {
+            timestamp: chrono::Utc::now(),
+            source: source.to_string(),
+            kind,
+            device,
+            detail,
+        });
+    }
+    fn end(&mut self) -> Option<PathBuf> {
+        self.pump(true);
+        let mut session = self.session.take()?;
+        let mut tail: Vec<f32> = Vec::new();
+        session.gate.finish(&mut tail);
+        if let Err(e) = session.writer.write(&tail) {
+            error!("Failed to write audio samples: {}", e);
+        }
+        let parts = session.writer.finish();
+        info!(
+            "Voice gate kept {} segment(s) over {}ms of audio",
+            session.gate.segment_count(),
+            session.gate.frames_to_ms(session.gate.source_frames())
+        );
+        let mut last: Option<PathBuf> = None;
+        for (part, sidecar) in AudioRecorder::describe_parts(&session, parts, &self.channel_names) {
+            if let Some(path) = AudioRecorder::commit_part(part, sidecar) {
+                if let Some(previous) = last.replace(path) {
+                    self.finished_segments.lock().unwrap().push(previous);
+                }
+            }
+        }
+        for link in self.links.iter_mut() {
+            if let Some(lost) = link.lost.as_mut() {
+                lost.gap = None;
+            }
+        }
+        last
+    }
+}
 pub struct AudioRecorder {
     is_recording: Arc<AtomicBool>,
-    current_file: Arc<Mutex<Option<PathBuf>>>,
-    recording_thread: Arc<Mutex<Option<JoinHandle<()>>>>,
+    armed: Arc<AtomicBool>,
+    finished_segments: Arc<Mutex<Vec<PathBuf>>>,
+    device_events: Arc<Mutex<Vec<DeviceEvent>>>,
+    commands: Arc<Mutex<Option<mpsc::Sender<CaptureCommand>>>>,
+    capture_thread: Arc<Mutex<Option<JoinHandle<()>>>>,
 }
 impl Default for AudioRecorder {
     fn default() -> Self {
@@ -21,239 +483,220 @@ impl AudioRecorder {
     pub fn new() -> Self {
         Self {
             is_recording: Arc::new(AtomicBool::new(false)),
-            current_file: Arc::new(Mutex::new(None)),
-            recording_thread: Arc::new(Mutex::new(None)),
+            armed: Arc::new(AtomicBool::new(false)),
+            finished_segments: Arc::new(Mutex::new(Vec::new())),
+            device_events: Arc::new(Mutex::new(Vec::new())),
+            commands: Arc::new(Mutex::new(None)),
+            capture_thread: Arc::new(Mutex::new(None)),
         }
     }
-    fn record_loop(
-        output_path: PathBuf,
-        sample_rate: u32,
-        channels: u16,
-        is_recording: Arc<AtomicBool>,
-        device_name: Option<String>,
-        silence_threshold: f32,
-    ) -> anyhow::Result<()> {
-        let part_path: PathBuf =
-            output_path.with_extension(crate::domain::constants::WAV_PART_EXTENSION);
-        let host: cpal::Host = cpal::default_host();
-        let device: cpal::Device = match device_name {
-            Some(name) => host
-                .input_devices()
-                .unwrap()
-                .find(|d| d.name().map(|n| n.contains(&name)).unwrap())
-                .unwrap(),
-            None => host.default_input_device().unwrap(),
-        };
-        let supported_configs = device.supported_input_configs().unwrap();
-        let config = supported_configs
-            .filter(|c| {
-                c.sample_format() == cpal::SampleFormat::F32
-                    || c.sample_format() == cpal::SampleFormat::I16
-            })
-            .find(|c| {
-                c.channels() == channels
-                    && c.min_sample_rate().0 <= sample_rate
-                    && c.max_sample_rate().0 >= sample_rate
-            })
-            .map(|c| c.with_sample_rate(cpal::SampleRate(sample_rate)).config())
-            .unwrap();
-        let sample_format: cpal::SampleFormat = device
-            .supported_input_configs()
-            .unwrap()
-            .find(|c| {
-                c.channels() == config.channels
-                    && c.min_sample_rate().0 <= config.sample_rate.0
-                    && c.max_sample_rate().0 >= config.sample_rate.0
-            })
-            .map(|c| c.sample_format())
-            .unwrap();
-        if let Some(parent) = part_path.parent() {
-            std::fs::create_dir_all(parent).map_err(|e| {
-                anyhow::anyhow!("Failed to create recording directory {:?}: {}", parent, e)
-            })?;
+    fn spawn_capture(
+        &self,
+        config: RecordingConfig,
+        pre_roll_secs: u64,
+        persistent: bool,
+    ) -> Result<(), String> {
+        let (tx, rx) = mpsc::channel::<CaptureCommand>();
+        let (ready_tx, ready_rx) = mpsc::channel::<Result<(), String>>();
+        let finished_segments = self.finished_segments.clone();
+        let device_events = self.device_events.clone();
+        let handle: JoinHandle<()> = thread::spawn(move || {
+            let mut capture =
+                match Capture::open(&config, pre_roll_secs, finished_segments, device_events) {
+                    Ok(capture) => capture,
+                    Err(e) => {
+                        error!("Recording failed: {}", e);
+                        let _ = ready_tx.send(Err(e.to_string()));
+                        return;
+                    }
+                };
+            let _ = ready_tx.send(Ok(()));
+            Self::capture_loop(&mut capture, rx, persistent);
+        });
+        *self.commands.lock().unwrap() = Some(tx);
+        *self.capture_thread.lock().unwrap() = Some(handle);
+        let opened = ready_rx
+            .recv()
+            .unwrap_or_else(|_| Err("Capture thread exited before opening".to_string()));
+        if opened.is_err() {
+            self.join_capture();
         }
-        let spec: hound::WavSpec = hound::WavSpec {
-            channels: config.channels,
-            sample_rate: config.sample_rate.0,
-            bits_per_sample: 16,
-            sample_format: hound::SampleFormat::Int,
-        };
-        let writer: Arc<Mutex<Option<hound::WavWriter<std::io::BufWriter<std::fs::File>>>>> =
-            Arc::new(Mutex::new(Some(
-                hound::WavWriter::create(&part_path, spec)
-                    .map_err(|e| anyhow::anyhow!("Failed to open {:?}: {}", part_path, e))?,
-            )));
-        let writer_cb = writer.clone();
-        let last_log: Arc<Mutex<Instant>> = Arc::new(Mutex::new(Instant::now()));
-        let peak_amplitude: Arc<Mutex<f32>> = Arc::new(Mutex::new(0.0f32));
-        let stream: cpal::Stream = match sample_format {
-            cpal::SampleFormat::F32 => device
-                .build_input_stream(
-                    &config,
-                    move |data: &[f32], _: &cpal::InputCallbackInfo| {
-                        Self::process_audio(
-                            data.iter().cloned(),
-                            &writer_cb,
-                            silence_threshold,
-                            &peak_amplitude,
-                            &last_log,
-                        );
-                    },
-                    |err| error!("Audio stream error: {}", err),
-                    None,
-                )
-                .unwrap(),
-            cpal::SampleFormat::I16 => device
-                .build_input_stream(
-                    &config,
-                    move |data: &[i16], _: &cpal::InputCallbackInfo| {
-                        let iter = data.iter().map(|&s| s as f32 / i16::MAX as f32);
-                        Self::process_audio(
-                            iter,
-                            &writer_cb,
-                            silence_threshold,
-                            &peak_amplitude,
-                            &last_log,
-                        );
-                    },
-                    |err| error!("Audio stream error: {}", err),
-                    None,
-                )
-                .unwrap(),
-            cpal::SampleFormat::U8 => device
-                .build_input_stream(
-                    &config,
-                    move |data: &[u8], _: &cpal::InputCallbackInfo| {
-                        let iter = data.iter().map(|&s| (s as f32 - 128.0) / 128.0);
-                        Self::process_audio(
-                            iter,
-                            &writer_cb,
-                            silence_threshold,
-                            &peak_amplitude,
-                            &last_log,
-                        );
-                    },
-                    |err| error!("Audio stream error: {}", err),
-                    None,
-                )
-                .unwrap(),
-            _ => {
-                error!("Unsupported sample format: {:?}", sample_format);
-                return Err(anyhow::anyhow!(
-                    "Unsupported sample format: {:?}",
-                    sample_format
-                ));
-            }
-        };
-        stream.play().unwrap();
-        while is_recording.load(Ordering::SeqCst) {
-            thread::sleep(Duration::from_millis(
+        opened
+    }
+    fn capture_loop(capture: &mut Capture, rx: mpsc::Receiver<CaptureCommand>, persistent: bool) {
+        loop {
+            match rx.recv_timeout(Duration::from_millis(
                 crate::domain::constants::AUDIO_SLEEP_MS,
-            ));
+            )) {
+                Ok(CaptureCommand::Start(path, recording, reply)) => {
+                    let started = if capture.session.is_some() {
+                        Err("Recording already in progress".to_string())
+                    } else {
+                        capture.begin(path, recording).map_err(|e| e.to_string())
+                    };
+                    if let Err(e) = &started {
+                        error!("Recording failed: {}", e);
+                    }
+                    let _ = reply.send(started);
+                }
+                Ok(CaptureCommand::Stop(reply)) => {
+                    let _ = reply.send(capture.end());
+                    if !persistent {
+                        return;
+                    }
+                }
+                Ok(CaptureCommand::Shutdown) | Err(mpsc::RecvTimeoutError::Disconnected) => {
+                    if let Some(path) = capture.end() {
+                        capture.finished_segments.lock().unwrap().push(path);
+                    }
+                    return;
+                }
+                Err(mpsc::RecvTimeoutError::Timeout) => {}
+            }
+            capture.pump(false);
         }
-        drop(stream);
-        let mut guard = writer.lock().unwrap();
-        if let Some(w) = guard.take() {
-            w.finalize().unwrap();
+    }
+    fn send(&self, command: CaptureCommand) -> bool {
+        self.commands
+            .lock()
+            .unwrap()
+            .as_ref()
+            .is_some_and(|tx| tx.send(command).is_ok())
+    }
+    fn capture_alive(&self) -> bool {
+        self.capture_thread
+            .lock()
+            .unwrap()
+            .as_ref()
+            .is_some_and(|handle| !handle.is_finished())
+    }
+    fn join_capture(&self) {
+        self.commands.lock().unwrap().take();
+        let handle = self.capture_thread.lock().unwrap().take();
+        if let Some(handle) = handle {
+            handle.join().unwrap();
         }
-        Ok(())
     }
-    fn process_audio<I>(
-        samples: I,
-        writer_cb: &Arc<Mutex<Option<hound::WavWriter<std::io::BufWriter<std::fs::File>>>>>,
-        _silence_threshold: f32,
-        peak_amplitude: &Arc<Mutex<f32>>,
-        last_log: &Arc<Mutex<Instant>>,
-    ) where
-        I: Iterator<Item = f32>,
-    {
-        let mut local_peak: f32 = 0.0f32;
-        let mut samples_to_write: Vec<i16> = Vec::new();
-        for sample in samples {
-            let abs_sample: f32 = sample.abs();
-            if abs_sample > local_peak {
-                local_peak = abs_sample;
+    fn describe_parts(
+        session: &Session,
+        parts: Vec<RetiredPart>,
+        channel_names: &[String],
+    ) -> Vec<(RetiredPart, SegmentSidecar)> {
+        let gate = &session.gate;
+        let recording = &session.recording;
+        parts
+            .into_iter()
+            .map(|part| {
+                let source_start_ms = gate.frames_to_ms(gate.source_at(part.file_start));
+                let source_end_ms = gate.frames_to_ms(gate.source_at(part.file_end));
+                let sidecar = SegmentSidecar {
+                    started_at: session.started_at,
+                    session_id: recording.session_id.clone(),
+                    part: part.index,
+                    sample_rate: recording.sample_rate,
+                    channels: channel_names.to_vec(),
+                    mode: recording.vad_mode,
+                    silence_threshold: recording.silence_threshold,
+                    source_start_ms,
+                    source_end_ms,
+                    file_duration_ms: gate.frames_to_ms(part.file_end - part.file_start),
+                    segments: gate.segments_in(part.file_start, part.file_end),
+                    gaps: session
+                        .gaps
+                        .iter()
+                        .filter(|g| {
+                            g.source_end_ms >= source_start_ms && g.source_start_ms <= source_end_ms
+                        })
+                        .cloned()
+                        .collect(),
+                };
+                (part, sidecar)
+            })
+            .collect()
+    }
+    fn commit_part(part: RetiredPart, sidecar: SegmentSidecar) -> Option<PathBuf> {
+        let final_path = part.final_path.clone();
+        match part.commit() {
+            Ok(path) => {
+                sidecar.save(&path);
+                info!("Recording segment committed: {:?}", path);
+                Some(path)
             }
-            let s: i16 = (sample.clamp(-1.0, 1.0) * i16::MAX as f32) as i16;
-            samples_to_write.push(s);
-        }
-        if !samples_to_write.is_empty() {
-            let mut guard = writer_cb.lock().unwrap();
-            if let Some(w) = guard.as_mut() {
-                for s in samples_to_write {
-                    w.write_sample(s).unwrap();
-                }
+            Err(e) => {
+                error!("Failed to commit recording segment {:?}: {}", final_path, e);
+                None
             }
         }
-        let mut peak = peak_amplitude.lock().unwrap();
-        if local_peak > *peak {
-            *peak = local_peak;
-        }
-        let mut last = last_log.lock().unwrap();
-        if last.elapsed() >= Duration::from_secs(crate::domain::constants::AUDIO_LOG_INTERVAL_SECS)
-        {
-            let p = peak_amplitude.lock().unwrap();
-            info!("Recording status: peak_amplitude={:.4}", *p);
-            *last = Instant::now();
-            drop(p);
-            let mut p_reset = peak_amplitude.lock().unwrap();
-            *p_reset = 0.0;
-        }
     }
 }
 impl AudioRecorderTrait for AudioRecorder {
-    fn start(
-        &self,
-        output_path: PathBuf,
-        sample_rate: u32,
-        channels: u16,
-        device_name: Option<String>,
-        silence_threshold: f32,
-    ) {
-        if self.is_recording.load(Ordering::SeqCst) {
+    fn arm(&self, config: RecordingConfig) {
+        if config.pre_roll_secs == 0 || self.armed.swap(true, Ordering::SeqCst) {
             return;
         }
-        self.is_recording.store(true, Ordering::SeqCst);
-        let mut current_file = self.current_file.lock().unwrap();
-        *current_file = Some(output_path.clone());
-        let is_recording: Arc<AtomicBool> = self.is_recording.clone();
-        let handle: JoinHandle<()> = thread::spawn(move || {
-            let part_path: PathBuf = output_path.clone();
-            if let Err(e) = Self::record_loop(
-                part_path,
-                sample_rate,
-                channels,
-                is_recording,
-                device_name,
-                silence_threshold,
-            ) {
-                error!("Recording failed: {}", e);
-            }
-        });
-        let mut recording_thread = self.recording_thread.lock().unwrap();
-        *recording_thread = Some(handle);
+        info!(
+            "Arming recorder with a {}s pre-roll buffer",
+            config.pre_roll_secs
+        );
+        let pre_roll_secs = config.pre_roll_secs;
+        if self.spawn_capture(config, pre_roll_secs, true).is_err() {
+            self.armed.store(false, Ordering::SeqCst);
+        }
     }
-    fn stop(&self) -> Option<PathBuf> {
+    fn disarm(&self) {
+        if !self.armed.swap(false, Ordering::SeqCst) {
+            return;
+        }
+        self.send(CaptureCommand::Shutdown);
+        self.join_capture();
         self.is_recording.store(false, Ordering::SeqCst);
-        let join_handle = {
-            let mut recording_thread = self.recording_thread.lock().unwrap();
-            recording_thread.take()
+    }
+    fn start(&self, output_path: PathBuf, config: RecordingConfig) -> Result<(), String> {
+        if self.is_recording.load(Ordering::SeqCst) {
+            return Err("Recording already in progress".to_string());
+        }
+        if self.armed.load(Ordering::SeqCst) && !self.capture_alive() {
+            warn!("Pre-roll capture is not running; opening a fresh capture");
+            self.armed.store(false, Ordering::SeqCst);
+            self.join_capture();
+        }
+        if !self.armed.load(Ordering::SeqCst) {
+            self.spawn_capture(config.clone(), 0, false)?;
+        }
+        let (reply_tx, reply_rx) = mpsc::channel();
+        let started = if self.send(CaptureCommand::Start(output_path, config, reply_tx)) {
+            reply_rx
+                .recv()
+                .unwrap_or_else(|_| Err("Capture thread exited".to_string()))
+        } else {
+            Err("Capture thread is not running".to_string())
         };
-        if let Some(handle) = join_handle {
-            handle.join().unwrap();
+        match started {
+            Ok(()) => self.is_recording.store(true, Ordering::SeqCst),
+            Err(_) if !self.armed.load(Ordering::SeqCst) => self.join_capture(),
+            Err(_) => {}
         }
-        let mut current_file = self.current_file.lock().unwrap();
-        let path: Option<PathBuf> = current_file.take();
-        if let Some(ref p) = path {
-            let part_path: PathBuf = p.with_extension(crate::domain::constants::WAV_PART_EXTENSION);
-            if part_path.exists() {
-                std::fs::rename(&part_path, p).unwrap();
-                if let Some(parent) = p.parent() {
-                    let dir: std::fs::File = std::fs::File::open(parent).unwrap();
-                    dir.sync_all().unwrap();
-                }
-            }
+        started
+    }
+    fn stop(&self) -> Option<PathBuf> {
+        if !self.is_recording.swap(false, Ordering::SeqCst) {
+            return None;
         }
-        path
+        let (reply_tx, reply_rx) = mpsc::channel();
+        let last = if self.send(CaptureCommand::Stop(reply_tx)) {
+            reply_rx.recv().ok().flatten()
+        } else {
+            None
+        };
+        if !self.armed.load(Ordering::SeqCst) {
+            self.join_capture();
+        }
+        last
+    }
+    fn take_finished_segments(&self) -> Vec<PathBuf> {
+        std::mem::take(&mut *self.finished_segments.lock().unwrap())
+    }
+    fn take_device_events(&self) -> Vec<DeviceEvent> {
+        std::mem::take(&mut *self.device_events.lock().unwrap())
     }
 }
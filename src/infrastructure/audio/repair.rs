@@ -0,0 +1,200 @@
+use std::fs::OpenOptions;
+use std::io::{BufReader, Read, Seek, SeekFrom, Write};
+use std::path::Path;
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RepairOutcome {
+    Intact,
+    Repaired { data_bytes: u64, trimmed_bytes: u64 },
+    Unrecoverable(String),
+}
+pub fn repair_recording(path: &Path) -> RepairOutcome {
+    let name = path.to_string_lossy();
+    let result = if name.ends_with(crate::domain::constants::WAV_PART_EXTENSION) {
+        repair_wav(path)
+    } else if name.ends_with(crate::domain::constants::FLAC_PART_EXTENSION) {
+        repair_flac(path)
+    } else {
+        Ok(RepairOutcome::Intact)
+    };
+    result.unwrap_or_else(|e| RepairOutcome::Unrecoverable(e.to_string()))
+}
+fn repair_wav(path: &Path) -> anyhow::Result<RepairOutcome> {
+    let mut file = OpenOptions::new().read(true).write(true).open(path)?;
+    let file_len = file.metadata()?.len();
+    let mut header = [0u8; 12];
+    if file_len < 12 || file.read_exact(&mut header).is_err() {
+        return Ok(RepairOutcome::Unrecoverable(
+            "file shorter than RIFF header".into(),
+        ));
+    }
+    if &header[0..4] != b"RIFF" || &header[8..12] != b"WAVE" {
+        return Ok(RepairOutcome::Unrecoverable(
+            "missing RIFF/WAVE signature".into(),
+        ));
+    }
+    let mut offset: u64 = 12;
+    let mut block_align: u64 = 0;
+    let data_header = loop {
+        if offset + 8 > file_len {
+            return Ok(RepairOutcome::Unrecoverable("no data chunk".into()));
+        }
+        let mut chunk = [0u8; 8];
+        file.seek(SeekFrom::Start(offset))?;
+        file.read_exact(&mut chunk)?;
+        let size = u32::from_le_bytes([chunk[4], chunk[5], chunk[6], chunk[7]]) as u64;
+        match &chunk[0..4] {
+            b"data" => break offset,
+            b"fmt " => {
+                let mut fmt = [0u8; 16];
+                if size < 16 || file.read_exact(&mut fmt).is_err() {
+                    return Ok(RepairOutcome::Unrecoverable("truncated fmt chunk".into()));
+                }
+                block_align = u16::from_le_bytes([fmt[12], fmt[13]]) as u64;
+            }
+            _ => {}
+        }
+        offset += 8 + size + size % 2;
+    };
+    if block_align == 0 {
+        return Ok(RepairOutcome::Unrecoverable("missing fmt chunk".into()));
+    }
+    let data_start = data_header + 8;
+    let available = file_len.saturating_sub(data_start);
+    let data_bytes = available - available % block_align;
+    if data_bytes == 0 {
+        return Ok(RepairOutcome::Unrecoverable("no audio frames".into()));
+    }
+    if data_start + data_bytes - 8 > u32::MAX as u64 {
+        return Ok(RepairOutcome::Unrecoverable(
+            "data exceeds RIFF size limit".into(),
+        ));
+    }
+    let riff_size = (data_start + data_bytes - 8) as u32;
+    let data_size = data_bytes as u32;
+    let mut current = [0u8; 4];
+    file.seek(SeekFrom::Start(4))?;
+    file.read_exact(&mut current)?;
+    let riff_ok = u32::from_le_bytes(current) == riff_size;
+    file.seek(SeekFrom::Start(data_header + 4))?;
+    file.read_exact(&mut current)?;
+    let data_ok = u32::from_le_bytes(current) == data_size;
+    let trimmed_bytes = available - data_bytes;
+    if riff_ok && data_ok && trimmed_bytes == 0 {
+        return Ok(RepairOutcome::Intact);
+    }
+    file.set_len(data_start + data_bytes)?;
+    file.seek(SeekFrom::Start(4))?;
+    file.write_all(&riff_size.to_le_bytes())?;
+    file.seek(SeekFrom::Start(data_header + 4))?;
+    file.write_all(&data_size.to_le_bytes())?;
+    file.sync_all()?;
+    Ok(RepairOutcome::Repaired {
+        data_bytes,
+        trimmed_bytes,
+    })
+}
+fn repair_flac(path: &Path) -> anyhow::Result<RepairOutcome> {
+    let mut file = OpenOptions::new().read(true).write(true).open(path)?;
+    let file_len = file.metadata()?.len();
+    let mut reader = BufReader::new(&file);
+    let mut signature = [0u8; 4];
+    if reader.read_exact(&mut signature).is_err() || &signature != b"fLaC" {
+        return Ok(RepairOutcome::Unrecoverable(
+            "missing fLaC signature".into(),
+        ));
+    }
+    let mut offset: u64 = 4;
+    let mut stream_info = None;
+    loop {
+        let mut block_header = [0u8; 4];
+        if reader.read_exact(&mut block_header).is_err() {
+            return Ok(RepairOutcome::Unrecoverable("truncated metadata".into()));
+        }
+        let block_len =
+            u32::from_be_bytes([0, block_header[1], block_header[2], block_header[3]]) as u64;
+        if block_header[0] & 0x7f == 0 && block_len >= 34 {
+            stream_info = Some(offset + 4);
+        }
+        offset += 4 + block_len;
+        reader.seek_relative(block_len as i64)?;
+        if block_header[0] & 0x80 != 0 {
+            break;
+        }
+    }
+    let Some(stream_info) = stream_info.filter(|_| offset <= file_len) else {
+        return Ok(RepairOutcome::Unrecoverable("missing STREAMINFO".into()));
+    };
+    let audio_start = offset;
+    let mut frames = CountingReader {
+        inner: reader,
+        position: 0,
+    };
+    let mut valid_end: u64 = 0;
+    let mut total_samples: u64 = 0;
+    let mut buffer = Vec::new();
+    while let Ok(Some(block)) =
+        claxon::frame::FrameReader::new(&mut frames).read_next_or_eof(std::mem::take(&mut buffer))
+    {
+        total_samples += block.duration() as u64;
+        valid_end = frames.position;
+        buffer = block.into_buffer();
+    }
+    drop(frames);
+    if total_samples == 0 {
+        return Ok(RepairOutcome::Unrecoverable("no audio frames".into()));
+    }
+    let total_field = stream_info + 10;
+    let mut field = [0u8; 8];
+    file.seek(SeekFrom::Start(total_field))?;
+    file.read_exact(&mut field)?;
+    let packed = u64::from_be_bytes(field);
+    let recorded_samples = packed & 0xF_FFFF_FFFF;
+    let available = file_len - audio_start;
+    let trimmed_bytes = available - valid_end;
+    if recorded_samples == total_samples && trimmed_bytes == 0 {
+        return Ok(RepairOutcome::Intact);
+    }
+    file.set_len(audio_start + valid_end)?;
+    file.seek(SeekFrom::Start(total_field))?;
+    file.write_all(&((packed & !0xF_FFFF_FFFF) | total_samples).to_be_bytes())?;
+    file.write_all(&[0u8; 16])?;
+    file.sync_all()?;
+    Ok(RepairOutcome::Repaired {
+        data_bytes: valid_end,
+        trimmed_bytes,
+    })
+}
+struct CountingReader<R> {
+    inner: R,
+    position: u64,
+}
+impl<R: Read> claxon::input::ReadBytes for CountingReader<R> {
+    fn read_u8(&mut self) -> std::io::Result<u8> {
+        let mut byte = [0u8; 1];
+        self.read_into(&mut byte)?;
+        Ok(byte[0])
+    }
+    fn read_u8_or_eof(&mut self) -> std::io::Result<Option<u8>> {
+        match self.read_u8() {
+            Ok(byte) => Ok(Some(byte)),
+            Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+    fn read_into(&mut self, buffer: &mut [u8]) -> std::io::Result<()> {
+        self.inner.read_exact(buffer)?;
+        self.position += buffer.len() as u64;
+        Ok(())
+    }
+    fn skip(&mut self, amount: u32) -> std::io::Result<()> {
+        let skipped = std::io::copy(
+            &mut (&mut self.inner).take(amount as u64),
+            &mut std::io::sink(),
+        )?;
+        self.position += skipped;
+        if skipped < amount as u64 {
+            return Err(std::io::ErrorKind::UnexpectedEof.into());
+        }
+        Ok(())
+    }
+}
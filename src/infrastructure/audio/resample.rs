@@ -0,0 +1,245 @@
+use std::io::Cursor;
+use std::path::Path;
+pub struct Resampler {
+    in_rate: u64,
+    out_rate: u64,
+    half: i64,
+    phases: u64,
+    table: Vec<f32>,
+    input: Vec<f32>,
+    offset: u64,
+    received: u64,
+    produced: u64,
+}
+impl Resampler {
+    pub fn new(in_rate: u32, out_rate: u32) -> Self {
+        let (in_rate, out_rate) = (in_rate.max(1) as u64, out_rate.max(1) as u64);
+        let g = gcd(in_rate, out_rate);
+        let phases = out_rate / g;
+        let cutoff = (out_rate as f64 / in_rate as f64).min(1.0);
+        let half =
+            (crate::domain::constants::RESAMPLER_ZERO_CROSSINGS as f64 / cutoff).ceil() as i64;
+        let taps = (2 * half) as usize;
+        let mut table: Vec<f32> = Vec::with_capacity(phases as usize * taps);
+        for p in 0..phases {
+            let phase = p as f64 / phases as f64;
+            let row: Vec<f64> = (-half + 1..=half)
+                .map(|m| {
+                    let d = phase - m as f64;
+                    let x = cutoff * d;
+                    let sinc = if x.abs() < 1e-9 {
+                        1.0
+                    } else {
+                        (std::f64::consts::PI * x).sin() / (std::f64::consts::PI * x)
+                    };
+                    let u = d / half as f64;
+                    let window = if u.abs() >= 1.0 {
+                        0.0
+                    } else {
+                        0.5 * (1.0 + (std::f64::consts::PI * u).cos())
+                    };
+                    cutoff * sinc * window
+                })
+                .collect();
+            let sum: f64 = row.iter().sum();
+            table.extend(row.iter().map(|w| (w / sum) as f32));
+        }
+        Self {
+            in_rate,
+            out_rate,
+            half,
+            phases,
+            table,
+            input: Vec::new(),
+            offset: 0,
+            received: 0,
+            produced: 0,
+        }
+    }
+    pub fn process(&mut self, samples: &[f32], out: &mut Vec<f32>) {
+        self.input.extend_from_slice(samples);
+        self.received += samples.len() as u64;
+        self.emit(u64::MAX, out);
+    }
+    pub fn finish(&mut self, out: &mut Vec<f32>) {
+        let expected = (self.received * self.out_rate).div_ceil(self.in_rate);
+        if self.in_rate != self.out_rate {
+            self.input
+                .extend(std::iter::repeat_n(0.0, self.half as usize + 1));
+        }
+        self.emit(expected, out);
+    }
+    fn emit(&mut self, limit: u64, out: &mut Vec<f32>) {
+        if self.in_rate == self.out_rate {
+            out.append(&mut self.input);
+            return;
+        }
+        let taps = (2 * self.half) as usize;
+        let g = self.out_rate / self.phases;
+        let available = self.offset as i64 + self.input.len() as i64;
+        while self.produced < limit {
+            let position = self.produced * self.in_rate;
+            let base = (position / self.out_rate) as i64;
+            if base + self.half >= available {
+                break;
+            }
+            let phase = ((position % self.out_rate) / g) as usize;
+            let row = &self.table[phase * taps..(phase + 1) * taps];
+            let mut acc = 0.0f32;
+            for (i, w) in row.iter().enumerate() {
+                let j = base - self.half + 1 + i as i64 - self.offset as i64;
+                if j >= 0 {
+                    acc += self.input[j as usize] * w;
+                }
+            }
+            out.push(acc);
+            self.produced += 1;
+        }
+        let next_base = (self.produced * self.in_rate / self.out_rate) as i64;
+        let keep_from = (next_base - self.half + 1).max(self.offset as i64) as u64;
+        let drop = ((keep_from - self.offset) as usize).min(self.input.len());
+        self.input.drain(..drop);
+        self.offset += drop as u64;
+    }
+}
+fn gcd(a: u64, b: u64) -> u64 {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+fn is_flac(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|e| e.eq_ignore_ascii_case("flac"))
+}
+fn probe(path: &Path) -> anyhow::Result<(u32, usize)> {
+    if is_flac(path) {
+        let reader = claxon::FlacReader::open(path)
+            .map_err(|e| anyhow::anyhow!("Failed to open {:?}: {}", path, e))?;
+        let info = reader.streaminfo();
+        return Ok((info.sample_rate, info.channels as usize));
+    }
+    let reader = hound::WavReader::open(path)
+        .map_err(|e| anyhow::anyhow!("Failed to open {:?}: {}", path, e))?;
+    Ok((
+        reader.spec().sample_rate,
+        reader.spec().channels.max(1) as usize,
+    ))
+}
+fn decode<F>(path: &Path, mut on_chunk: F) -> anyhow::Result<()>
+where
+    F: FnMut(&[f32], usize),
+{
+    let chunk_frames = crate::domain::constants::TRANSCRIBE_CHUNK_FRAMES;
+    let mut chunk: Vec<f32> = Vec::new();
+    if is_flac(path) {
+        let mut reader = claxon::FlacReader::open(path)
+            .map_err(|e| anyhow::anyhow!("Failed to open {:?}: {}", path, e))?;
+        let info = reader.streaminfo();
+        let channels = info.channels as usize;
+        let scale = (1u64 << (info.bits_per_sample - 1)) as f32;
+        for sample in reader.samples() {
+            chunk.push(sample? as f32 / scale);
+            if chunk.len() >= chunk_frames * channels {
+                on_chunk(&chunk, channels);
+                chunk.clear();
+            }
+        }
+        on_chunk(&chunk, channels);
+        return Ok(());
+    }
+    let mut reader = hound::WavReader::open(path)
+        .map_err(|e| anyhow::anyhow!("Failed to open {:?}: {}", path, e))?;
+    let spec = reader.spec();
+    let channels = spec.channels.max(1) as usize;
+    match spec.sample_format {
+        hound::SampleFormat::Float => {
+            for sample in reader.samples::<f32>() {
+                chunk.push(sample?);
+                if chunk.len() >= chunk_frames * channels {
+                    on_chunk(&chunk, channels);
+                    chunk.clear();
+                }
+            }
+        }
+        hound::SampleFormat::Int => {
+            let scale = (1u64 << (spec.bits_per_sample - 1)) as f32;
+            for sample in reader.samples::<i32>() {
+                chunk.push(sample? as f32 / scale);
+                if chunk.len() >= chunk_frames * channels {
+                    on_chunk(&chunk, channels);
+                    chunk.clear();
+                }
+            }
+        }
+    }
+    on_chunk(&chunk, channels);
+    Ok(())
+}
+pub fn prepare_for_transcription(path: &Path, keep_channels: bool) -> anyhow::Result<Vec<u8>> {
+    let (sample_rate, channels) = probe(path)?;
+    let out_channels = if keep_channels {
+        channels
+    } else {
+        crate::domain::constants::TARGET_CHANNELS as usize
+    };
+    let target_rate = crate::domain::constants::TARGET_SAMPLE_RATE;
+    let mut resamplers: Vec<Resampler> = (0..out_channels)
+        .map(|_| Resampler::new(sample_rate, target_rate))
+        .collect();
+    let spec = hound::WavSpec {
+        channels: out_channels as u16,
+        sample_rate: target_rate,
+        bits_per_sample: crate::domain::constants::DEFAULT_BITS_PER_SAMPLE,
+        sample_format: hound::SampleFormat::Int,
+    };
+    let mut bytes: Cursor<Vec<u8>> = Cursor::new(Vec::new());
+    let mut writer = hound::WavWriter::new(&mut bytes, spec)?;
+    let mut planes: Vec<Vec<f32>> = vec![Vec::new(); out_channels];
+    let mut resampled: Vec<Vec<f32>> = vec![Vec::new(); out_channels];
+    let mut write_error: Option<hound::Error> = None;
+    let mut flush = |resampled: &mut Vec<Vec<f32>>, writer: &mut hound::WavWriter<_>| {
+        let frames = resampled.iter().map(|r| r.len()).min().unwrap_or(0);
+        for i in 0..frames {
+            for plane in resampled.iter() {
+                let value = (plane[i].clamp(-1.0, 1.0) * i16::MAX as f32) as i16;
+                if let Err(e) = writer.write_sample(value) {
+                    write_error.get_or_insert(e);
+                }
+            }
+        }
+        for plane in resampled.iter_mut() {
+            plane.drain(..frames);
+        }
+    };
+    decode(path, |chunk, source_channels| {
+        for plane in planes.iter_mut() {
+            plane.clear();
+        }
+        for frame in chunk.chunks_exact(source_channels) {
+            if out_channels == source_channels {
+                for (plane, sample) in planes.iter_mut().zip(frame) {
+                    plane.push(*sample);
+                }
+            } else {
+                planes[0].push(frame.iter().sum::<f32>() / source_channels as f32);
+            }
+        }
+        for ((resampler, plane), out) in
+            resamplers.iter_mut().zip(&planes).zip(resampled.iter_mut())
+        {
+            resampler.process(plane, out);
+        }
+        flush(&mut resampled, &mut writer);
+    })?;
+    for (resampler, out) in resamplers.iter_mut().zip(resampled.iter_mut()) {
+        resampler.finish(out);
+    }
+    flush(&mut resampled, &mut writer);
+    if let Some(e) = write_error {
+        return Err(e.into());
+    }
+    writer.finalize()?;
+    Ok(bytes.into_inner())
+}
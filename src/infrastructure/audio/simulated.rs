@@ -0,0 +1,225 @@
+use super::mixer::SourceBuffer;
+use super::resample::Resampler;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum SyntheticKind {
+    Tone,
+    Noise,
+    Silence,
+}
+#[derive(Debug, Clone, Copy)]
+struct SyntheticStep {
+    kind: SyntheticKind,
+    frames: u64,
+}
+enum Signal {
+    Fixture {
+        samples: Vec<f32>,
+        position: usize,
+    },
+    Synthetic {
+        steps: Vec<SyntheticStep>,
+        step: usize,
+        step_frame: u64,
+        phase: f32,
+        seed: u32,
+    },
+}
+impl Signal {
+    fn fill(&mut self, frames: usize, channels: usize, sample_rate: u32, out: &mut Vec<f32>) {
+        match self {
+            Signal::Fixture { samples, position } => {
+                for _ in 0..frames * channels {
+                    out.push(samples[*position]);
+                    *position = (*position + 1) % samples.len();
+                }
+            }
+            Signal::Synthetic {
+                steps,
+                step,
+                step_frame,
+                phase,
+                seed,
+            } => {
+                let amplitude = crate::domain::constants::SYNTHETIC_AMPLITUDE;
+                let increment = crate::domain::constants::SYNTHETIC_TONE_HZ * std::f32::consts::TAU
+                    / sample_rate as f32;
+                for _ in 0..frames {
+                    let current = steps[*step];
+                    let value = match current.kind {
+                        SyntheticKind::Tone => {
+                            *phase = (*phase + increment) % std::f32::consts::TAU;
+                            phase.sin() * amplitude
+                        }
+                        SyntheticKind::Noise => {
+                            *seed ^= *seed << 13;
+                            *seed ^= *seed >> 17;
+                            *seed ^= *seed << 5;
+                            (*seed as f32 / u32::MAX as f32 * 2.0 - 1.0) * amplitude
+                        }
+                        SyntheticKind::Silence => 0.0,
+                    };
+                    out.extend(std::iter::repeat_n(value, channels));
+                    *step_frame += 1;
+                    if current.frames > 0 && *step_frame >= current.frames {
+                        *step_frame = 0;
+                        *step = (*step + 1) % steps.len();
+                    }
+                }
+            }
+        }
+    }
+}
+fn parse_pattern(pattern: &str, sample_rate: u32) -> anyhow::Result<Vec<SyntheticStep>> {
+    let steps = pattern
+        .split(',')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(|step| {
+            let (kind, secs) = match step.split_once(':') {
+                Some((kind, secs)) => (
+                    kind.trim(),
+                    secs.trim().parse::<f32>().map_err(|_| {
+                        anyhow::anyhow!("Invalid duration in synthetic pattern step '{}'", step)
+                    })?,
+                ),
+                None => (step, 0.0),
+            };
+            let kind = match kind {
+                "tone" => SyntheticKind::Tone,
+                "noise" => SyntheticKind::Noise,
+                "silence" => SyntheticKind::Silence,
+                other => anyhow::bail!("Unknown synthetic pattern '{}'", other),
+            };
+            Ok(SyntheticStep {
+                kind,
+                frames: (secs * sample_rate as f32) as u64,
+            })
+        })
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    if steps.is_empty() {
+        anyhow::bail!("Synthetic pattern is empty");
+    }
+    Ok(steps)
+}
+fn load_fixture(path: &Path, sample_rate: u32) -> anyhow::Result<(Vec<f32>, usize)> {
+    let mut reader = hound::WavReader::open(path)
+        .map_err(|e| anyhow::anyhow!("Failed to open fixture {:?}: {}", path, e))?;
+    let spec = reader.spec();
+    let channels = spec.channels.max(1) as usize;
+    let interleaved: Vec<f32> = match spec.sample_format {
+        hound::SampleFormat::Float => reader.samples::<f32>().collect::<Result<_, _>>()?,
+        hound::SampleFormat::Int => {
+            let scale = (1u64 << (spec.bits_per_sample - 1)) as f32;
+            reader
+                .samples::<i32>()
+                .map(|s| s.map(|v| v as f32 / scale))
+                .collect::<Result<_, _>>()?
+        }
+    };
+    if spec.sample_rate == sample_rate {
+        return Ok((interleaved, channels));
+    }
+    let mut planes: Vec<Vec<f32>> = Vec::with_capacity(channels);
+    for c in 0..channels {
+        let plane: Vec<f32> = interleaved
+            .iter()
+            .skip(c)
+            .step_by(channels)
+            .copied()
+            .collect();
+        let mut resampler = Resampler::new(spec.sample_rate, sample_rate);
+        let mut out: Vec<f32> = Vec::new();
+        resampler.process(&plane, &mut out);
+        resampler.finish(&mut out);
+        planes.push(out);
+    }
+    let frames = planes.iter().map(|p| p.len()).min().unwrap_or(0);
+    let samples = (0..frames)
+        .flat_map(|i| planes.iter().map(move |p| p[i]))
+        .collect();
+    Ok((samples, channels))
+}
+pub struct SimulatedInput {
+    signal: Signal,
+    pub channels: u16,
+}
+impl SimulatedInput {
+    pub fn fixture(path: &Path, sample_rate: u32) -> anyhow::Result<Self> {
+        let (samples, channels) = load_fixture(path, sample_rate)?;
+        if samples.is_empty() {
+            anyhow::bail!("Fixture {:?} contains no audio", path);
+        }
+        Ok(Self {
+            signal: Signal::Fixture {
+                samples,
+                position: 0,
+            },
+            channels: channels as u16,
+        })
+    }
+    pub fn synthetic(pattern: &str, channels: u16, sample_rate: u32) -> anyhow::Result<Self> {
+        Ok(Self {
+            signal: Signal::Synthetic {
+                steps: parse_pattern(pattern, sample_rate)?,
+                step: 0,
+                step_frame: 0,
+                phase: 0.0,
+                seed: 0x9e37_79b9,
+            },
+            channels: channels.max(1),
+        })
+    }
+}
+pub struct Feeder {
+    running: Arc<AtomicBool>,
+    handle: Option<JoinHandle<()>>,
+}
+impl Feeder {
+    pub fn start(
+        input: SimulatedInput,
+        sample_rate: u32,
+        buffer: Arc<Mutex<SourceBuffer>>,
+        out_channels: usize,
+    ) -> Self {
+        let mut signal = input.signal;
+        let channels = input.channels as usize;
+        let running = Arc::new(AtomicBool::new(true));
+        let flag = running.clone();
+        let handle = std::thread::spawn(move || {
+            let started = Instant::now();
+            let mut pushed: u64 = 0;
+            let mut samples: Vec<f32> = Vec::new();
+            while flag.load(Ordering::SeqCst) {
+                std::thread::sleep(Duration::from_millis(
+                    crate::domain::constants::VAD_FRAME_MS as u64,
+                ));
+                let due = started.elapsed().as_millis() as u64 * sample_rate as u64 / 1000;
+                let frames = (due - pushed) as usize;
+                samples.clear();
+                signal.fill(frames, channels, sample_rate, &mut samples);
+                buffer
+                    .lock()
+                    .unwrap()
+                    .push(&samples, channels, out_channels);
+                pushed = due;
+            }
+        });
+        Self {
+            running,
+            handle: Some(handle),
+        }
+    }
+}
+impl Drop for Feeder {
+    fn drop(&mut self) {
+        self.running.store(false, Ordering::SeqCst);
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
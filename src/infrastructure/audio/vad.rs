@@ -0,0 +1,272 @@
+use crate::domain::GateMode;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct SpeechSegment {
+    pub source_start_ms: u64,
+    pub source_end_ms: u64,
+    pub file_start_ms: u64,
+    pub file_end_ms: u64,
+}
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct DeviceGap {
+    pub source: String,
+    pub source_start_ms: u64,
+    pub source_end_ms: u64,
+    #[serde(default)]
+    pub reopened_on: Option<String>,
+}
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SegmentSidecar {
+    pub started_at: DateTime<Utc>,
+    #[serde(default)]
+    pub session_id: Option<String>,
+    #[serde(default)]
+    pub part: u32,
+    pub sample_rate: u32,
+    #[serde(default)]
+    pub channels: Vec<String>,
+    pub mode: GateMode,
+    pub silence_threshold: f32,
+    #[serde(default)]
+    pub source_start_ms: u64,
+    pub source_end_ms: u64,
+    pub file_duration_ms: u64,
+    pub segments: Vec<SpeechSegment>,
+    #[serde(default)]
+    pub gaps: Vec<DeviceGap>,
+}
+impl SegmentSidecar {
+    pub fn path_for(audio_path: &Path) -> PathBuf {
+        audio_path.with_extension(crate::domain::constants::SEGMENTS_SIDECAR_EXTENSION)
+    }
+    pub fn load(audio_path: &Path) -> Option<Self> {
+        let content = std::fs::read_to_string(Self::path_for(audio_path)).ok()?;
+        serde_json::from_str(&content).ok()
+    }
+    pub fn save(&self, audio_path: &Path) {
+        let path = Self::path_for(audio_path);
+        match serde_json::to_string_pretty(self) {
+            Ok(content) => crate::infrastructure::fs_utils::atomic_write(path, content),
+            Err(e) => tracing::warn!("Failed to serialize segment sidecar {:?}: {}", path, e),
+        }
+    }
+    pub fn wall_clock_at(&self, file_ms: u64) -> DateTime<Utc> {
+        let source_ms = self
+            .segments
+            .iter()
+            .find(|s| file_ms >= s.file_start_ms && file_ms <= s.file_end_ms)
+            .map(|s| s.source_start_ms + (file_ms - s.file_start_ms))
+            .or_else(|| {
+                self.segments
+                    .iter()
+                    .rev()
+                    .find(|s| s.file_end_ms <= file_ms)
+                    .map(|s| s.source_end_ms)
+            })
+            .unwrap_or(self.source_start_ms + file_ms);
+        self.started_at + chrono::Duration::milliseconds(source_ms as i64)
+    }
+    pub fn part_started_at(&self) -> DateTime<Utc> {
+        self.started_at + chrono::Duration::milliseconds(self.source_start_ms as i64)
+    }
+    pub fn ended_at(&self) -> DateTime<Utc> {
+        self.started_at + chrono::Duration::milliseconds(self.source_end_ms as i64)
+    }
+}
+#[derive(Debug, Clone, Copy)]
+struct FrameSegment {
+    source_start: u64,
+    file_start: u64,
+    file_end: u64,
+}
+pub struct VoiceGate {
+    mode: GateMode,
+    threshold: f32,
+    sample_rate: u32,
+    channels: usize,
+    frame_len: usize,
+    pending: Vec<f32>,
+    pre_roll: VecDeque<Vec<f32>>,
+    pre_roll_frames: usize,
+    hangover_frames: usize,
+    silent_run: usize,
+    open_segment: Option<(u64, u64)>,
+    last_close_frames: u64,
+    source_frames: u64,
+    file_frames: u64,
+    segments: Vec<FrameSegment>,
+}
+impl VoiceGate {
+    pub fn new(
+        sample_rate: u32,
+        channels: u16,
+        threshold: f32,
+        hangover_ms: u64,
+        pre_roll_ms: u64,
+        mode: GateMode,
+    ) -> Self {
+        let frame_ms = crate::domain::constants::VAD_FRAME_MS as u64;
+        let frames_per_chunk = (sample_rate as u64 * frame_ms / 1000).max(1) as usize;
+        Self {
+            mode,
+            threshold,
+            sample_rate,
+            channels: channels.max(1) as usize,
+            frame_len: frames_per_chunk * channels.max(1) as usize,
+            pending: Vec::new(),
+            pre_roll: VecDeque::new(),
+            pre_roll_frames: pre_roll_ms.div_ceil(frame_ms) as usize,
+            hangover_frames: hangover_ms.div_ceil(frame_ms) as usize,
+            silent_run: 0,
+            open_segment: None,
+            last_close_frames: 0,
+            source_frames: 0,
+            file_frames: 0,
+            segments: Vec::new(),
+        }
+    }
+    pub fn push(&mut self, samples: &[f32], out: &mut Vec<f32>) {
+        self.pending.extend_from_slice(samples);
+        let mut offset = 0;
+        while self.pending.len() - offset >= self.frame_len {
+            let frame: Vec<f32> = self.pending[offset..offset + self.frame_len].to_vec();
+            offset += self.frame_len;
+            self.process_frame(frame, out);
+        }
+        self.pending.drain(..offset);
+    }
+    pub fn finish(&mut self, out: &mut Vec<f32>) {
+        if !self.pending.is_empty() {
+            let frame = std::mem::take(&mut self.pending);
+            self.process_frame(frame, out);
+        }
+        self.close_segment();
+    }
+    pub fn segment_count(&self) -> usize {
+        self.segments.len() + usize::from(self.open_segment.is_some())
+    }
+    pub fn source_frames(&self) -> u64 {
+        self.source_frames
+    }
+    pub fn frames_to_ms(&self, frames: u64) -> u64 {
+        frames * 1000 / self.sample_rate.max(1) as u64
+    }
+    pub fn source_at(&self, file_frame: u64) -> u64 {
+        if self.mode == GateMode::Mark {
+            return file_frame;
+        }
+        let mut source = 0;
+        for seg in self.all_segments() {
+            if file_frame < seg.file_start {
+                break;
+            }
+            source = seg.source_start + (file_frame.min(seg.file_end) - seg.file_start);
+        }
+        source
+    }
+    pub fn segments_in(&self, file_start: u64, file_end: u64) -> Vec<SpeechSegment> {
+        self.all_segments()
+            .filter(|seg| seg.file_end > file_start && seg.file_start < file_end)
+            .map(|seg| {
+                let start = seg.file_start.max(file_start);
+                let end = seg.file_end.min(file_end);
+                SpeechSegment {
+                    source_start_ms: self.frames_to_ms(seg.source_start + (start - seg.file_start)),
+                    source_end_ms: self.frames_to_ms(seg.source_start + (end - seg.file_start)),
+                    file_start_ms: self.frames_to_ms(start - file_start),
+                    file_end_ms: self.frames_to_ms(end - file_start),
+                }
+            })
+            .collect()
+    }
+    fn all_segments(&self) -> impl Iterator<Item = FrameSegment> + '_ {
+        let open = self
+            .open_segment
+            .map(|(source_start, file_start)| FrameSegment {
+                source_start,
+                file_start,
+                file_end: self.file_frames,
+            });
+        self.segments.iter().copied().chain(open)
+    }
+    fn process_frame(&mut self, frame: Vec<f32>, out: &mut Vec<f32>) {
+        let frame_frames = (frame.len() / self.channels) as u64;
+        let voiced = self.is_voiced(&frame);
+        if voiced {
+            self.silent_run = 0;
+            if self.open_segment.is_none() {
+                self.open_at_speech_start(out);
+            }
+            self.emit(frame, out);
+        } else if self.open_segment.is_some() {
+            self.emit(frame, out);
+            self.silent_run += 1;
+            if self.silent_run >= self.hangover_frames {
+                self.source_frames += frame_frames;
+                self.close_segment();
+                return;
+            }
+        } else {
+            match self.mode {
+                GateMode::Drop => {
+                    self.pre_roll.push_back(frame);
+                    while self.pre_roll.len() > self.pre_roll_frames {
+                        self.pre_roll.pop_front();
+                    }
+                }
+                GateMode::Mark => self.emit(frame, out),
+            }
+        }
+        self.source_frames += frame_frames;
+    }
+    fn open_at_speech_start(&mut self, out: &mut Vec<f32>) {
+        match self.mode {
+            GateMode::Drop => {
+                let buffered: u64 = self
+                    .pre_roll
+                    .iter()
+                    .map(|f| (f.len() / self.channels) as u64)
+                    .sum();
+                let source_start = self.source_frames - buffered;
+                let file_start = self.file_frames;
+                while let Some(f) = self.pre_roll.pop_front() {
+                    self.emit(f, out);
+                }
+                self.open_segment = Some((source_start, file_start));
+            }
+            GateMode::Mark => {
+                let pre_roll = (self.pre_roll_frames * self.frame_len / self.channels) as u64;
+                let start = self
+                    .source_frames
+                    .saturating_sub(pre_roll)
+                    .max(self.last_close_frames);
+                self.open_segment = Some((start, start));
+            }
+        }
+    }
+    fn close_segment(&mut self) {
+        if let Some((source_start, file_start)) = self.open_segment.take() {
+            self.segments.push(FrameSegment {
+                source_start,
+                file_start,
+                file_end: self.file_frames,
+            });
+            self.last_close_frames = self.source_frames;
+        }
+        self.silent_run = 0;
+    }
+    fn emit(&mut self, frame: Vec<f32>, out: &mut Vec<f32>) {
+        self.file_frames += (frame.len() / self.channels) as u64;
+        out.extend(frame);
+    }
+    fn is_voiced(&self, frame: &[f32]) -> bool {
+        if self.threshold <= 0.0 {
+            return true;
+        }
+        let energy: f32 = frame.iter().map(|s| s * s).sum::<f32>() / frame.len().max(1) as f32;
+        energy.sqrt() >= self.threshold
+    }
+}
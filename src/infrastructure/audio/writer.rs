@@ -0,0 +1,186 @@
+use super::flac::FlacWriter;
+use crate::domain::AudioFormat;
+use std::path::{Path, PathBuf};
+type WavFileWriter = hound::WavWriter<std::io::BufWriter<std::fs::File>>;
+enum PartEncoder {
+    Wav(WavFileWriter),
+    Flac(Box<FlacWriter>),
+}
+impl PartEncoder {
+    fn create(path: &Path, format: AudioFormat, spec: hound::WavSpec) -> anyhow::Result<Self> {
+        let encoder = match format {
+            AudioFormat::Wav => PartEncoder::Wav(
+                hound::WavWriter::create(path, spec)
+                    .map_err(|e| anyhow::anyhow!("Failed to open {:?}: {}", path, e))?,
+            ),
+            AudioFormat::Flac => PartEncoder::Flac(Box::new(FlacWriter::create(
+                path,
+                spec.sample_rate,
+                spec.channels,
+            )?)),
+        };
+        Ok(encoder)
+    }
+    fn write(&mut self, samples: &[i16]) -> anyhow::Result<()> {
+        match self {
+            PartEncoder::Wav(w) => {
+                for s in samples {
+                    w.write_sample(*s)?;
+                }
+            }
+            PartEncoder::Flac(w) => w.write_samples(samples)?,
+        }
+        Ok(())
+    }
+    fn finalize(self) -> anyhow::Result<()> {
+        match self {
+            PartEncoder::Wav(w) => w.finalize()?,
+            PartEncoder::Flac(w) => w.finalize()?,
+        }
+        Ok(())
+    }
+}
+pub struct RetiredPart {
+    pub index: u32,
+    pub final_path: PathBuf,
+    pub file_start: u64,
+    pub file_end: u64,
+    writer: PartEncoder,
+}
+impl RetiredPart {
+    pub fn commit(self) -> anyhow::Result<PathBuf> {
+        self.writer
+            .finalize()
+            .map_err(|e| anyhow::anyhow!("Failed to finalize {:?}: {}", self.final_path, e))?;
+        commit_part(&part_path_for(&self.final_path), &self.final_path)?;
+        Ok(self.final_path)
+    }
+}
+pub struct SegmentWriter {
+    base_path: PathBuf,
+    format: AudioFormat,
+    spec: hound::WavSpec,
+    max_frames: u64,
+    index: u32,
+    current: Option<PartEncoder>,
+    part_start: u64,
+    written: u64,
+    retired: Vec<RetiredPart>,
+}
+impl SegmentWriter {
+    pub fn create(
+        base_path: PathBuf,
+        format: AudioFormat,
+        spec: hound::WavSpec,
+        max_secs: u64,
+    ) -> anyhow::Result<Self> {
+        let mut writer = Self {
+            base_path: base_path.with_extension(format.extension()),
+            format,
+            spec,
+            max_frames: max_secs * spec.sample_rate as u64,
+            index: 0,
+            current: None,
+            part_start: 0,
+            written: 0,
+            retired: Vec::new(),
+        };
+        writer.open_next()?;
+        Ok(writer)
+    }
+    pub fn write(&mut self, samples: &[f32]) -> anyhow::Result<()> {
+        let channels = self.spec.channels.max(1) as usize;
+        let mut remaining = samples;
+        while !remaining.is_empty() {
+            let frames = (remaining.len() / channels) as u64;
+            let room = if self.max_frames == 0 {
+                frames
+            } else {
+                (self.max_frames - (self.written - self.part_start)).min(frames)
+            };
+            let split = if frames == 0 {
+                remaining.len()
+            } else {
+                room as usize * channels
+            };
+            let (head, tail) = remaining.split_at(split);
+            if let Some(w) = self.current.as_mut() {
+                let converted: Vec<i16> = head.iter().map(|s| to_i16(*s)).collect();
+                w.write(&converted)?;
+            }
+            self.written += (head.len() / channels) as u64;
+            remaining = tail;
+            if self.max_frames > 0 && self.written - self.part_start >= self.max_frames {
+                self.rotate()?;
+            }
+        }
+        Ok(())
+    }
+    pub fn take_retired(&mut self) -> Vec<RetiredPart> {
+        std::mem::take(&mut self.retired)
+    }
+    pub fn finish(&mut self) -> Vec<RetiredPart> {
+        self.retire_current();
+        std::mem::take(&mut self.retired)
+    }
+    fn rotate(&mut self) -> anyhow::Result<()> {
+        self.retire_current();
+        tracing::info!(
+            "Recording reached {}s, rolling over to part {}",
+            self.max_frames / self.spec.sample_rate.max(1) as u64,
+            self.index + 1
+        );
+        self.open_next()
+    }
+    fn retire_current(&mut self) {
+        if let Some(writer) = self.current.take() {
+            self.retired.push(RetiredPart {
+                index: self.index,
+                final_path: segment_path(&self.base_path, self.index),
+                file_start: self.part_start,
+                file_end: self.written,
+                writer,
+            });
+        }
+    }
+    fn open_next(&mut self) -> anyhow::Result<()> {
+        self.index += 1;
+        self.part_start = self.written;
+        let part_path = part_path_for(&segment_path(&self.base_path, self.index));
+        self.current = Some(PartEncoder::create(&part_path, self.format, self.spec)?);
+        Ok(())
+    }
+}
+pub fn segment_path(base_path: &Path, index: u32) -> PathBuf {
+    if index <= 1 {
+        return base_path.to_path_buf();
+    }
+    let stem = base_path
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let ext = base_path
+        .extension()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default();
+    base_path.with_file_name(format!("{}_{:03}.{}", stem, index, ext))
+}
+pub fn part_path_for(final_path: &Path) -> PathBuf {
+    let ext = final_path
+        .extension()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default();
+    final_path.with_extension(format!("{}.part", ext))
+}
+pub fn commit_part(part_path: &Path, final_path: &Path) -> anyhow::Result<()> {
+    std::fs::rename(part_path, final_path)
+        .map_err(|e| anyhow::anyhow!("Failed to commit {:?}: {}", part_path, e))?;
+    if let Some(parent) = final_path.parent() {
+        let dir: std::fs::File = std::fs::File::open(parent)?;
+        dir.sync_all()?;
+    }
+    Ok(())
+}
+fn to_i16(sample: f32) -> i16 {
+    (sample.clamp(-1.0, 1.0) * i16::MAX as f32) as i16
+}
@@ -0,0 +1,98 @@
+use crate::domain::{ControlCommand, ControlReply, ControlRequest};
+use std::net::SocketAddr;
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::{mpsc, oneshot};
+use tracing::{info, warn};
+pub async fn listen(addr: &str) -> anyhow::Result<mpsc::Receiver<ControlRequest>> {
+    let addr: SocketAddr = addr
+        .parse()
+        .map_err(|e| anyhow::anyhow!("Invalid control socket address {}: {}", addr, e))?;
+    if !addr.ip().is_loopback() {
+        anyhow::bail!("Control socket {} must bind to a loopback address", addr);
+    }
+    let listener = TcpListener::bind(addr)
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to bind control socket {}: {}", addr, e))?;
+    info!("Control socket listening on {}", addr);
+    let (tx, rx) = mpsc::channel(crate::domain::constants::CONTROL_QUEUE_DEPTH);
+    tokio::spawn(async move {
+        loop {
+            match listener.accept().await {
+                Ok((stream, peer)) => {
+                    let tx = tx.clone();
+                    tokio::spawn(async move {
+                        if let Err(e) = serve(stream, tx).await {
+                            warn!("Control connection {} failed: {}", peer, e);
+                        }
+                    });
+                }
+                Err(e) => warn!("Control socket accept failed: {}", e),
+            }
+        }
+    });
+    Ok(rx)
+}
+async fn serve(stream: TcpStream, tx: mpsc::Sender<ControlRequest>) -> anyhow::Result<()> {
+    let (reader, mut writer) = stream.into_split();
+    let mut reader = BufReader::new(reader);
+    let mut buf = Vec::new();
+    loop {
+        buf.clear();
+        let limit = crate::domain::constants::CONTROL_MAX_LINE_BYTES;
+        let read = (&mut reader)
+            .take(limit as u64 + 1)
+            .read_until(b'\n', &mut buf)
+            .await?;
+        if read == 0 {
+            break;
+        }
+        if buf.len() > limit && !buf.ends_with(b"\n") {
+            anyhow::bail!("Control command exceeds {} bytes", limit);
+        }
+        let line = String::from_utf8_lossy(&buf);
+        if line.trim().is_empty() {
+            continue;
+        }
+        let reply = match serde_json::from_str::<ControlCommand>(&line) {
+            Ok(command) => {
+                let (reply_tx, reply_rx) = oneshot::channel();
+                let request = ControlRequest {
+                    command,
+                    reply: reply_tx,
+                };
+                if tx.send(request).await.is_err() {
+                    ControlReply::error("Monitor is shutting down")
+                } else {
+                    reply_rx
+                        .await
+                        .unwrap_or_else(|_| ControlReply::error("Monitor dropped the request"))
+                }
+            }
+            Err(e) => ControlReply::error(format!("Invalid control command: {}", e)),
+        };
+        let mut payload = serde_json::to_string(&reply)?;
+        payload.push('\n');
+        writer.write_all(payload.as_bytes()).await?;
+    }
+    Ok(())
+}
+pub async fn send(addr: &str, command: &ControlCommand) -> anyhow::Result<ControlReply> {
+    let stream = TcpStream::connect(addr).await.map_err(|e| {
+        anyhow::anyhow!(
+            "Failed to connect to monitor control socket {}: {} (is `vlog-rs monitor` running?)",
+            addr,
+            e
+        )
+    })?;
+    let (reader, mut writer) = stream.into_split();
+    let mut payload = serde_json::to_string(command)?;
+    payload.push('\n');
+    writer.write_all(payload.as_bytes()).await?;
+    let line = BufReader::new(reader)
+        .lines()
+        .next_line()
+        .await?
+        .ok_or_else(|| anyhow::anyhow!("Monitor closed the control connection"))?;
+    Ok(serde_json::from_str(&line)?)
+}
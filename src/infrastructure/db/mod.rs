@@ -5,35 +5,66 @@ use tracing::{error, info};
 pub struct EventRepository {
     pool: SqlitePool,
 }
-impl EventRepository {
-    pub async fn new(db_path: &str) -> Self {
-        let db_url = if db_path.starts_with("sqlite:") {
-            db_path.to_string()
-        } else if db_path == ":memory:" {
-            "sqlite::memory:".to_string()
-        } else {
-            format!("sqlite:{}", db_path)
-        };
-        info!("Connecting to SQLite: {}", db_url);
-        let options = SqliteConnectOptions::from_str(&db_url)
-            .unwrap()
-            .create_if_missing(true)
-            .journal_mode(sqlx::sqlite::SqliteJournalMode::Delete)
-            .busy_timeout(std::time::Duration::from_secs(10));
-        let pool = match SqlitePool::connect_with(options).await {
-            Ok(p) => p,
-            Err(e) => {
-                error!("CRITICAL: Failed to connect to SQLite at {}: {}", db_url, e);
+pub async fn connect(db_path: &str) -> SqlitePool {
+    let db_url = if db_path.starts_with("sqlite:") {
+        db_path.to_string()
+    } else if db_path == ":memory:" {
+        "sqlite::memory:".to_string()
+    } else {
+        format!("sqlite:{}", db_path)
+    };
+    info!("Connecting to SQLite: {}", db_url);
+    let options = SqliteConnectOptions::from_str(&db_url)
+        .unwrap()
+        .create_if_missing(true)
+        .journal_mode(sqlx::sqlite::SqliteJournalMode::Delete)
+        .busy_timeout(std::time::Duration::from_secs(10));
+    let pool = match SqlitePool::connect_with(options).await {
+        Ok(p) => p,
+        Err(e) => {
+            error!("CRITICAL: Failed to connect to SQLite at {}: {}", db_url, e);
+            std::process::exit(1);
+        }
+    };
+    info!("DB Connected. Applying schema...");
+    if let Err(e) = sqlx::query(include_str!("schema.sql")).execute(&pool).await {
+        error!("CRITICAL: Schema application failed: {}", e);
+        std::process::exit(1);
+    }
+    migrate_task_columns(&pool).await;
+    pool
+}
+async fn migrate_task_columns(pool: &SqlitePool) {
+    let existing: Vec<String> = match sqlx::query("PRAGMA table_info(tasks)")
+        .fetch_all(pool)
+        .await
+    {
+        Ok(rows) => rows.iter().map(|row| sqlx::Row::get(row, "name")).collect(),
+        Err(e) => {
+            error!("CRITICAL: Failed to inspect the tasks table: {}", e);
+            std::process::exit(1);
+        }
+    };
+    for (column, ddl) in crate::domain::constants::SQL_TASK_COLUMN_MIGRATIONS {
+        if !existing.iter().any(|c| c == column) {
+            info!("Migrating tasks table: adding column {}", column);
+            if let Err(e) = sqlx::query(ddl).execute(pool).await {
+                error!("CRITICAL: Failed to add tasks column {}: {}", column, e);
                 std::process::exit(1);
             }
-        };
-        info!("DB Connected. Applying schema...");
-        if let Err(e) = sqlx::query(include_str!("schema.sql")).execute(&pool).await {
-            error!("CRITICAL: Schema application failed: {}", e);
-            std::process::exit(1);
         }
+    }
+}
+impl EventRepository {
+    pub async fn new(db_path: &str) -> Self {
+        Self::from_pool(connect(db_path).await)
+    }
+    pub fn from_pool(pool: SqlitePool) -> Self {
         Self { pool }
     }
+    pub fn pool(&self) -> SqlitePool {
+        self.pool.clone()
+    }
 }
 #[async_trait::async_trait]
 impl EventRepositoryTrait for EventRepository {
@@ -67,6 +98,7 @@ impl EventRepositoryTrait for EventRepository {
             let metadata_str: String = sqlx::Row::get(&row, "metadata");
             let source = match source_type_str.as_str() {
                 "WindowsAudio" => crate::domain::SourceType::WindowsAudio,
+                "AudioDevice" => crate::domain::SourceType::AudioDevice,
                 "WindowsActivity" => crate::domain::SourceType::WindowsActivity,
                 "UbuntuMonitor" => crate::domain::SourceType::UbuntuMonitor,
                 _ => crate::domain::SourceType::System,
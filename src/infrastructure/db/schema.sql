@@ -11,4 +11,23 @@ CREATE TABLE IF NOT EXISTS life_events (
 CREATE INDEX IF NOT EXISTS idx_life_events_timestamp ON life_events(timestamp);
 CREATE INDEX IF NOT EXISTS idx_life_events_source_type ON life_events(source_type);
 
+CREATE TABLE IF NOT EXISTS tasks (
+    id TEXT PRIMARY KEY,
+    created_at DATETIME NOT NULL,
+    status TEXT NOT NULL,
+    task_type TEXT NOT NULL,
+    file_paths TEXT NOT NULL, -- JSON array
+    session_id TEXT,
+    profile TEXT, -- JSON TaskProfile
+    lease_owner TEXT,
+    lease_expires_at DATETIME,
+    attempts INTEGER NOT NULL DEFAULT 0,
+    last_error TEXT,
+    next_attempt_at DATETIME,
+    depends_on TEXT NOT NULL DEFAULT '[]', -- JSON array of task ids
+    date TEXT -- YYYYMMDD for daily pipeline tasks
+);
+
+CREATE INDEX IF NOT EXISTS idx_tasks_status_created_at ON tasks(status, created_at);
+
 -- Milestone 54: WAL mode is enabled via connection options in Rust
@@ -30,7 +30,7 @@ impl Environment for LocalEnvironment {
             .prompt_with_default("Audio device name (blank = default)", "")
             .unwrap();
         let config = format!(
-            "process:\n  names: \"{}\"\n  check_interval: {}\npaths:\n  recording_dir: \"data/recordings\"\naudio:\n  device_name: {}\n  silence_threshold: 0.02\ntrigger:\n  start_debounce_secs: 2\n  stop_grace_secs: 10\n  min_recording_secs: 60\n",
+            "process:\n  names: \"{}\"\n  check_interval: {}\npaths:\n  recording_dir: \"data/recordings\"\naudio:\n  device_name: {}\n  format: \"wav\"\n  silence_threshold: 0.02\n  vad_hangover_ms: 800\n  vad_pre_roll_ms: 300\n  vad_mode: \"drop\"\n  pre_roll_secs: 0\n  backend: \"device\"\ntrigger:\n  start_debounce_secs: 2\n  stop_grace_secs: 10\n  min_recording_secs: 60\ncontrol:\n  addr: \"127.0.0.1:47820\"\n",
             process_names,
             check_interval,
             if device_name.is_empty() {
@@ -1,9 +1,18 @@
-use crate::domain::{Curator, Evaluation, Novelizer};
+use crate::domain::{
+    Curator, Evaluation, LlmError, LlmProvider, LlmProviderKind, LlmRouting, Novelizer,
+};
+use crate::infrastructure::llm_cache::LlmCache;
+use crate::infrastructure::llm_middleware::{ProviderChain, RateLimitedProvider};
 use crate::infrastructure::prompts::Prompts;
 use base64::{engine::general_purpose, Engine as _};
 use reqwest::Client;
 use serde_json::{json, Value};
-use std::process::Command;
+use std::collections::HashMap;
+use std::io::{Read, Seek, SeekFrom};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::process::Command;
+use tokio::sync::Semaphore;
 #[derive(Clone)]
 pub struct NoopGemini;
 impl Default for NoopGemini {
@@ -19,243 +28,903 @@ impl NoopGemini {
 }
 #[async_trait::async_trait]
 impl crate::domain::ContentGenerator for NoopGemini {
-    async fn generate_content(&self, _prompt: &str) -> String {
-        "".to_string()
+    async fn generate_content(&self, _prompt: &str) -> Result<String, LlmError> {
+        Ok("".to_string())
     }
-    async fn transcribe(&self, _file_path: &str) -> String {
-        "".to_string()
+    async fn transcribe(&self, _file_path: &str) -> Result<String, LlmError> {
+        Ok("".to_string())
     }
 }
 #[async_trait::async_trait]
 impl Curator for NoopGemini {
-    async fn evaluate(&self, _summary: &str, _novel: &str) -> Evaluation {
-        Evaluation {
+    async fn evaluate(&self, _summary: &str, _novel: &str) -> Result<Evaluation, LlmError> {
+        Ok(Evaluation {
             faithfulness_score: 0,
             quality_score: 0,
             reasoning: "Gemini disabled".to_string(),
-        }
+        })
     }
     async fn verify_summary(
         &self,
         _summary: &str,
         _transcript: &str,
         _activities: &str,
-    ) -> Evaluation {
-        Evaluation {
+    ) -> Result<Evaluation, LlmError> {
+        Ok(Evaluation {
             faithfulness_score: 0,
             quality_score: 0,
             reasoning: "Gemini disabled".to_string(),
-        }
+        })
+    }
+    async fn summarize_session(
+        &self,
+        _transcript: &str,
+        _activities: &str,
+        _variant: Option<&str>,
+    ) -> Result<String, LlmError> {
+        Ok("".to_string())
+    }
+}
+#[async_trait::async_trait]
+impl Novelizer for NoopGemini {
+    async fn generate_chapter(&self, _summary: &str, _context: &str) -> Result<String, LlmError> {
+        Ok("".to_string())
+    }
+}
+#[derive(Clone)]
+pub struct LlmBudget<T: ?Sized> {
+    inner: Arc<T>,
+    permits: Arc<Semaphore>,
+}
+impl<T: ?Sized> LlmBudget<T> {
+    pub fn new(inner: Arc<T>, permits: Arc<Semaphore>) -> Self {
+        Self { inner, permits }
+    }
+    async fn acquire(&self) -> Result<tokio::sync::SemaphorePermit<'_>, LlmError> {
+        self.permits
+            .acquire()
+            .await
+            .map_err(|e| LlmError::Transport(format!("LLM budget unavailable: {}", e)))
+    }
+}
+#[async_trait::async_trait]
+impl<T: crate::domain::ContentGenerator + ?Sized> crate::domain::ContentGenerator for LlmBudget<T> {
+    async fn generate_content(&self, prompt: &str) -> Result<String, LlmError> {
+        let _permit = self.acquire().await?;
+        self.inner.generate_content(prompt).await
+    }
+    async fn transcribe(&self, file_path: &str) -> Result<String, LlmError> {
+        let _permit = self.acquire().await?;
+        self.inner.transcribe(file_path).await
+    }
+}
+#[async_trait::async_trait]
+impl<T: Curator + ?Sized> Curator for LlmBudget<T> {
+    async fn evaluate(&self, summary: &str, novel: &str) -> Result<Evaluation, LlmError> {
+        let _permit = self.acquire().await?;
+        self.inner.evaluate(summary, novel).await
     }
-    async fn summarize_session(&self, _transcript: &str, _activities: &str) -> String {
-        "".to_string()
+    async fn verify_summary(
+        &self,
+        summary: &str,
+        transcript: &str,
+        activities: &str,
+    ) -> Result<Evaluation, LlmError> {
+        let _permit = self.acquire().await?;
+        self.inner
+            .verify_summary(summary, transcript, activities)
+            .await
     }
+    async fn summarize_session(
+        &self,
+        transcript: &str,
+        activities: &str,
+        variant: Option<&str>,
+    ) -> Result<String, LlmError> {
+        let _permit = self.acquire().await?;
+        self.inner
+            .summarize_session(transcript, activities, variant)
+            .await
+    }
+}
+#[async_trait::async_trait]
+impl<T: Novelizer + ?Sized> Novelizer for LlmBudget<T> {
+    async fn generate_chapter(&self, summary: &str, context: &str) -> Result<String, LlmError> {
+        let _permit = self.acquire().await?;
+        self.inner.generate_chapter(summary, context).await
+    }
+}
+pub trait AudioSource: Read + Seek + Send {}
+impl<T: Read + Seek + Send> AudioSource for T {}
+#[derive(Debug, Clone, Copy)]
+pub struct PromptTemplate<'a> {
+    pub name: &'a str,
+    pub text: &'a str,
+}
+#[derive(Debug, Clone, Copy)]
+pub struct ResponseSchema<'a> {
+    pub name: &'a str,
+    pub schema: &'a Value,
+}
+pub enum LlmRequest<'a> {
+    Generate {
+        template: PromptTemplate<'a>,
+        inputs: &'a [&'a str],
+        prompt: &'a str,
+        schema: Option<ResponseSchema<'a>>,
+    },
+    Transcribe {
+        file_path: &'a str,
+        prompt: &'a str,
+    },
 }
 #[derive(Clone)]
-pub struct GeminiClient {
-    api_key: String,
-    model: String,
+pub struct ProviderClient {
+    provider: LlmProvider,
     client: Client,
-    prompts: Prompts,
 }
-impl GeminiClient {
-    pub fn new(api_key: String, model: String, prompts: Prompts) -> Self {
-        Self {
-            api_key,
-            model,
-            client: Client::new(),
-            prompts,
+impl ProviderClient {
+    pub fn new(provider: LlmProvider) -> Result<Self, LlmError> {
+        let client = Client::builder()
+            .timeout(Duration::from_secs(provider.timeout_secs))
+            .build()
+            .map_err(|e| {
+                LlmError::Transport(format!(
+                    "failed to build HTTP client for {}: {}",
+                    provider.name, e
+                ))
+            })?;
+        Ok(Self { provider, client })
+    }
+    pub fn provider(&self) -> &LlmProvider {
+        &self.provider
+    }
+    pub async fn send(&self, request: &LlmRequest<'_>) -> Result<String, LlmError> {
+        match *request {
+            LlmRequest::Generate { prompt, schema, .. } => self.generate(prompt, schema).await,
+            LlmRequest::Transcribe { file_path, prompt } => match self.provider.kind {
+                LlmProviderKind::Gemini => self.transcribe_file(file_path, prompt).await,
+                LlmProviderKind::Whisper => self.transcribe_with_whisper(file_path).await,
+                kind => Err(LlmError::Api {
+                    status: 400,
+                    message: format!(
+                        "LLM provider {} ({:?}) cannot transcribe audio",
+                        self.provider.name, kind
+                    ),
+                }),
+            },
         }
     }
-    pub async fn generate_content(&self, prompt: &str) -> String {
-        if let Some(model) = self.model.strip_prefix("ollama:") {
-            let body: Value = json!({
-                "model": model,
-                "prompt": prompt,
-                "stream": false
-            });
-            let resp = self
-                .client
-                .post("http://127.0.0.1:11434/api/generate")
-                .json(&body)
-                .send()
+    async fn generate(
+        &self,
+        prompt: &str,
+        schema: Option<ResponseSchema<'_>>,
+    ) -> Result<String, LlmError> {
+        match self.provider.kind {
+            LlmProviderKind::Gemini => {
+                let mut body: Value = json!({
+                    "contents": [{
+                        "parts": [{
+                            "text": prompt
+                        }]
+                    }]
+                });
+                if let Some(schema) = schema {
+                    body["generationConfig"] = json!({
+                        "responseMimeType": "application/json",
+                        "responseJsonSchema": schema.schema
+                    });
+                }
+                self.post_gemini(body).await
+            }
+            LlmProviderKind::Ollama => {
+                let mut body: Value = json!({
+                    "model": self.provider.model,
+                    "prompt": prompt,
+                    "stream": false
+                });
+                if let Some(schema) = schema {
+                    body["format"] = schema.schema.clone();
+                }
+                let url = format!("{}/api/generate", self.provider.base_url);
+                let parsed = self.post_json(&url, body).await?;
+                parsed["response"]
+                    .as_str()
+                    .map(str::to_string)
+                    .ok_or_else(|| LlmError::BadFormat(format!("Ollama response: {}", parsed)))
+            }
+            LlmProviderKind::OpenAi => {
+                let mut body: Value = json!({
+                    "model": self.provider.model,
+                    "messages": [{
+                        "role": "user",
+                        "content": prompt
+                    }]
+                });
+                if let Some(schema) = schema {
+                    body["response_format"] = json!({
+                        "type": "json_schema",
+                        "json_schema": {
+                            "name": schema.name,
+                            "schema": schema.schema,
+                            "strict": true
+                        }
+                    });
+                }
+                let url = format!("{}/chat/completions", self.provider.base_url);
+                let parsed = self.post_json(&url, body).await?;
+                let choice = &parsed["choices"][0];
+                if let Some(content) = choice["message"]["content"].as_str() {
+                    return Ok(content.to_string());
+                }
+                match choice["finish_reason"].as_str() {
+                    Some(reason @ "content_filter") => Err(LlmError::Blocked(reason.to_string())),
+                    _ => Err(LlmError::BadFormat(parsed.to_string())),
+                }
+            }
+            LlmProviderKind::Whisper => Err(LlmError::Api {
+                status: 400,
+                message: format!(
+                    "LLM provider {} (whisper) cannot generate text",
+                    self.provider.name
+                ),
+            }),
+        }
+    }
+    async fn transcribe_file(&self, file_path: &str, prompt: &str) -> Result<String, LlmError> {
+        let path = std::path::Path::new(file_path);
+        let sidecar = crate::infrastructure::audio::SegmentSidecar::load(path);
+        let keep_channels = sidecar.as_ref().is_some_and(|s| s.channels.len() > 1);
+        let ext: &str = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .ok_or_else(|| LlmError::BadFormat(format!("no audio extension on {}", file_path)))?;
+        let open = |file_path: &str| {
+            let file = std::fs::File::open(file_path)
+                .map_err(|e| LlmError::BadFormat(format!("failed to read {}: {}", file_path, e)))?;
+            let len = file
+                .metadata()
+                .map_err(|e| LlmError::BadFormat(format!("failed to read {}: {}", file_path, e)))?
+                .len();
+            Ok::<(Box<dyn AudioSource>, u64), LlmError>((Box::new(file), len))
+        };
+        let ((mut audio, len), mime_type): ((Box<dyn AudioSource>, u64), &str) = match ext {
+            "wav" | "flac" => {
+                let source = path.to_path_buf();
+                let prepared = tokio::task::spawn_blocking(move || {
+                    crate::infrastructure::audio::prepare_for_transcription(&source, keep_channels)
+                })
                 .await
-                .unwrap();
-            let status = resp.status();
-            let text: String = resp.text().await.unwrap();
-            if !status.is_success() {
-                panic!("Ollama API Error (Status {}): {}", status, text);
+                .unwrap_or_else(|e| Err(anyhow::anyhow!("resampling task failed: {}", e)));
+                match prepared {
+                    Ok(bytes) => {
+                        let len = bytes.len() as u64;
+                        ((Box::new(std::io::Cursor::new(bytes)), len), "audio/wav")
+                    }
+                    Err(e) => {
+                        tracing::warn!(
+                            "Resampling {} failed ({}); sending original audio",
+                            file_path,
+                            e
+                        );
+                        let mime = if ext == "flac" {
+                            "audio/flac"
+                        } else {
+                            "audio/wav"
+                        };
+                        (open(file_path)?, mime)
+                    }
+                }
             }
-            let parsed: Value = serde_json::from_str(&text).unwrap();
-            return parsed["response"].as_str().unwrap().to_string();
+            "mp3" => (open(file_path)?, "audio/mp3"),
+            _ => (open(file_path)?, "audio/wav"),
+        };
+        let mut prompt: String = prompt.to_string();
+        if let Some(sidecar) = sidecar.filter(|_| keep_channels) {
+            let layout: Vec<String> = sidecar
+                .channels
+                .iter()
+                .enumerate()
+                .map(|(i, name)| format!("channel {} = {}", i + 1, name))
+                .collect();
+            prompt.push_str(&format!(
+                "\n\nThis recording is multichannel ({}). Use the channel to attribute each utterance to its source.",
+                layout.join(", ")
+            ));
         }
-        let url: String = format!(
-            "https://generativelanguage.googleapis.com/v1beta/models/{}:generateContent?key={}",
-            self.model, self.api_key
-        );
-        let body: Value = json!({
-            "contents": [{
-                "parts": [{
-                    "text": prompt
-                }]
-            }]
-        });
-        self.post_and_parse(&url, body).await
+        self.transcribe_audio(&mut audio, len, mime_type, &prompt)
+            .await
     }
-    pub async fn transcribe_audio(&self, audio_data: &[u8], mime_type: &str) -> String {
-        let url: String = format!(
-            "https://generativelanguage.googleapis.com/v1beta/models/{}:generateContent?key={}",
-            self.model, self.api_key
+    pub async fn transcribe_audio(
+        &self,
+        audio: &mut dyn AudioSource,
+        len: u64,
+        mime_type: &str,
+        prompt: &str,
+    ) -> Result<String, LlmError> {
+        if len <= self.provider.inline_max_bytes {
+            let mut audio_data = Vec::with_capacity(len as usize);
+            audio
+                .read_to_end(&mut audio_data)
+                .map_err(|e| LlmError::BadFormat(format!("failed to read audio: {}", e)))?;
+            let base64_audio: String = general_purpose::STANDARD.encode(audio_data);
+            let audio_part = json!({
+                "inline_data": {
+                    "mime_type": mime_type,
+                    "data": base64_audio
+                }
+            });
+            return self.post_gemini(Self::audio_body(audio_part, prompt)).await;
+        }
+        tracing::info!(
+            "Audio is {} bytes (inline limit {}); uploading via the Files API",
+            len,
+            self.provider.inline_max_bytes
         );
-        let base64_audio: String = general_purpose::STANDARD.encode(audio_data);
-        let body: Value = json!({
+        let file = self.upload_file(audio, len, mime_type).await?;
+        let name = file["name"].as_str().map(str::to_string);
+        let result = match self.wait_until_active(file).await {
+            Ok(file) => {
+                let audio_part = json!({
+                    "file_data": {
+                        "mime_type": mime_type,
+                        "file_uri": file["uri"]
+                    }
+                });
+                self.post_gemini(Self::audio_body(audio_part, prompt)).await
+            }
+            Err(e) => Err(e),
+        };
+        if let Some(name) = name {
+            self.delete_file(&name).await;
+        }
+        result
+    }
+    fn audio_body(audio_part: Value, prompt: &str) -> Value {
+        json!({
             "contents": [{
                 "parts": [
+                    audio_part,
                     {
-                        "inline_data": {
-                            "mime_type": mime_type,
-                            "data": base64_audio
-                        }
-                    },
-                    {
-                        "text": &self.prompts.transcription
+                        "text": prompt
                     }
                 ]
             }]
-        });
-        self.post_and_parse(&url, body).await
+        })
     }
-    async fn post_and_parse(&self, url: &str, body: Value) -> String {
-        let resp = self.client.post(url).json(&body).send().await.unwrap();
+    async fn upload_file(
+        &self,
+        audio: &mut dyn AudioSource,
+        len: u64,
+        mime_type: &str,
+    ) -> Result<Value, LlmError> {
+        let url = format!(
+            "{}/files?key={}",
+            self.provider.upload_base_url,
+            self.provider.api_key.as_deref().unwrap_or_default()
+        );
+        let resp = self
+            .client
+            .post(&url)
+            .header("X-Goog-Upload-Protocol", "resumable")
+            .header("X-Goog-Upload-Command", "start")
+            .header("X-Goog-Upload-Header-Content-Length", len.to_string())
+            .header("X-Goog-Upload-Header-Content-Type", mime_type)
+            .json(&json!({ "file": { "display_name": format!("vlog-{}", uuid::Uuid::now_v7()) } }))
+            .send()
+            .await
+            .map_err(|e| LlmError::Transport(e.to_string()))?;
+        let resp = Self::check_status(resp).await?;
+        let upload_url = resp
+            .headers()
+            .get("x-goog-upload-url")
+            .and_then(|v| v.to_str().ok())
+            .map(str::to_string)
+            .ok_or_else(|| {
+                LlmError::BadFormat("upload start returned no upload URL".to_string())
+            })?;
+        let granularity = resp
+            .headers()
+            .get("x-goog-upload-chunk-granularity")
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.parse::<u64>().ok())
+            .filter(|g| *g > 0)
+            .unwrap_or(1);
+        let chunk = (crate::domain::constants::GEMINI_UPLOAD_CHUNK_BYTES as u64)
+            .div_ceil(granularity)
+            * granularity;
+        let mut offset = 0;
+        let mut resumes = 0;
+        loop {
+            let end = (offset + chunk).min(len);
+            let command = if end == len {
+                "upload, finalize"
+            } else {
+                "upload"
+            };
+            let body = Self::read_chunk(audio, offset, end)?;
+            let sent = self
+                .client
+                .post(&upload_url)
+                .header("X-Goog-Upload-Command", command)
+                .header("X-Goog-Upload-Offset", offset.to_string())
+                .body(body)
+                .send()
+                .await;
+            let error = match sent {
+                Ok(resp) if resp.status().is_success() => {
+                    if end == len {
+                        let text = resp
+                            .text()
+                            .await
+                            .map_err(|e| LlmError::Transport(e.to_string()))?;
+                        let parsed: Value = serde_json::from_str(&text)
+                            .map_err(|e| LlmError::BadFormat(format!("{}: {}", e, text)))?;
+                        return Ok(parsed["file"].clone());
+                    }
+                    offset = end;
+                    continue;
+                }
+                Ok(resp) if resp.status().is_server_error() => {
+                    let status = resp.status();
+                    LlmError::Transport(format!("upload chunk status {}", status))
+                }
+                Ok(resp) => return Err(Self::status_error(resp).await),
+                Err(e) => LlmError::Transport(e.to_string()),
+            };
+            resumes += 1;
+            if resumes > crate::domain::constants::GEMINI_UPLOAD_MAX_RESUMES {
+                return Err(error);
+            }
+            offset = self.query_upload_offset(&upload_url).await?;
+            if offset > len {
+                return Err(LlmError::BadFormat(format!(
+                    "upload query reported {} of {} bytes",
+                    offset, len
+                )));
+            }
+            tracing::warn!(
+                "Upload interrupted ({}); resuming at byte {} of {}",
+                error,
+                offset,
+                len
+            );
+        }
+    }
+    fn read_chunk(audio: &mut dyn AudioSource, start: u64, end: u64) -> Result<Vec<u8>, LlmError> {
+        let mut body = Vec::with_capacity((end - start) as usize);
+        audio
+            .seek(SeekFrom::Start(start))
+            .and_then(|_| audio.take(end - start).read_to_end(&mut body))
+            .map_err(|e| LlmError::BadFormat(format!("failed to read audio chunk: {}", e)))?;
+        if body.len() as u64 != end - start {
+            return Err(LlmError::BadFormat(format!(
+                "audio ended at byte {} before {}",
+                start + body.len() as u64,
+                end
+            )));
+        }
+        Ok(body)
+    }
+    async fn query_upload_offset(&self, upload_url: &str) -> Result<u64, LlmError> {
+        let resp = self
+            .client
+            .post(upload_url)
+            .header("X-Goog-Upload-Command", "query")
+            .send()
+            .await
+            .map_err(|e| LlmError::Transport(e.to_string()))?;
+        let resp = Self::check_status(resp).await?;
+        resp.headers()
+            .get("x-goog-upload-size-received")
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.parse::<u64>().ok())
+            .ok_or_else(|| {
+                LlmError::BadFormat("upload query returned no received size".to_string())
+            })
+    }
+    async fn wait_until_active(&self, mut file: Value) -> Result<Value, LlmError> {
+        let deadline = std::time::Instant::now()
+            + Duration::from_secs(crate::domain::constants::GEMINI_FILE_ACTIVE_TIMEOUT_SECS);
+        loop {
+            match file["state"].as_str() {
+                Some("PROCESSING") if std::time::Instant::now() < deadline => {}
+                Some("PROCESSING") => {
+                    return Err(LlmError::Transport(format!(
+                        "uploaded file {} still processing",
+                        file["name"]
+                    )))
+                }
+                Some("FAILED") => {
+                    return Err(LlmError::BadFormat(format!(
+                        "uploaded file failed: {}",
+                        file
+                    )))
+                }
+                _ if file["uri"].is_string() => return Ok(file),
+                _ => return Err(LlmError::BadFormat(format!("upload response: {}", file))),
+            }
+            tokio::time::sleep(Duration::from_secs(
+                crate::domain::constants::GEMINI_FILE_POLL_SECS,
+            ))
+            .await;
+            let name = file["name"].as_str().unwrap_or_default();
+            let url = format!(
+                "{}/{}?key={}",
+                self.provider.base_url,
+                name,
+                self.provider.api_key.as_deref().unwrap_or_default()
+            );
+            let resp = self
+                .client
+                .get(&url)
+                .send()
+                .await
+                .map_err(|e| LlmError::Transport(e.to_string()))?;
+            let text = Self::check_status(resp)
+                .await?
+                .text()
+                .await
+                .map_err(|e| LlmError::Transport(e.to_string()))?;
+            file = serde_json::from_str(&text)
+                .map_err(|e| LlmError::BadFormat(format!("{}: {}", e, text)))?;
+        }
+    }
+    async fn delete_file(&self, name: &str) {
+        let url = format!(
+            "{}/{}?key={}",
+            self.provider.base_url,
+            name,
+            self.provider.api_key.as_deref().unwrap_or_default()
+        );
+        match self.client.delete(&url).send().await {
+            Ok(resp) if resp.status().is_success() => {}
+            Ok(resp) => tracing::warn!("Failed to delete uploaded {}: {}", name, resp.status()),
+            Err(e) => tracing::warn!("Failed to delete uploaded {}: {}", name, e),
+        }
+    }
+    async fn check_status(resp: reqwest::Response) -> Result<reqwest::Response, LlmError> {
+        if resp.status().is_success() {
+            return Ok(resp);
+        }
+        Err(Self::status_error(resp).await)
+    }
+    async fn status_error(resp: reqwest::Response) -> LlmError {
+        let status = resp.status().as_u16();
+        let text = resp.text().await.unwrap_or_default();
+        Self::classify_status(status, &text, None)
+    }
+    async fn transcribe_with_whisper(&self, file_path: &str) -> Result<String, LlmError> {
+        let output_dir = "data/transcripts";
+        tokio::fs::create_dir_all(output_dir)
+            .await
+            .map_err(|e| LlmError::Transport(format!("failed to create {}: {}", output_dir, e)))?;
+        let stem = std::path::Path::new(file_path)
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .ok_or_else(|| LlmError::BadFormat(format!("no file name in {}", file_path)))?;
+        let mut command = Command::new(crate::domain::constants::WHISPER_CMD);
+        command
+            .arg(file_path)
+            .arg("--output_dir")
+            .arg(output_dir)
+            .arg("--output_format")
+            .arg("txt")
+            .arg("--task")
+            .arg("transcribe");
+        if !self.provider.model.is_empty() {
+            command.arg("--model").arg(&self.provider.model);
+        }
+        let status = command
+            .status()
+            .await
+            .map_err(|e| LlmError::Transport(format!("failed to run whisper: {}", e)))?;
+        if !status.success() {
+            return Err(LlmError::Transport(format!(
+                "whisper transcription failed with status {}",
+                status
+            )));
+        }
+        let transcript_path = format!("{}/{}.txt", output_dir, stem);
+        tokio::fs::read_to_string(&transcript_path)
+            .await
+            .map_err(|e| {
+                LlmError::BadFormat(format!("whisper produced no {}: {}", transcript_path, e))
+            })
+    }
+    async fn post_gemini(&self, body: Value) -> Result<String, LlmError> {
+        let url: String = format!(
+            "{}/models/{}:generateContent?key={}",
+            self.provider.base_url,
+            self.provider.model,
+            self.provider.api_key.as_deref().unwrap_or_default()
+        );
+        let parsed = self.post_json(&url, body).await?;
+        if let Some(reason) = parsed["promptFeedback"]["blockReason"].as_str() {
+            return Err(LlmError::Blocked(reason.to_string()));
+        }
+        let candidate = &parsed["candidates"][0];
+        if let Some(content) = candidate["content"]["parts"][0]["text"].as_str() {
+            return Ok(content.to_string());
+        }
+        match candidate["finishReason"].as_str() {
+            Some(
+                reason @ ("SAFETY" | "PROHIBITED_CONTENT" | "BLOCKLIST" | "SPII" | "RECITATION"),
+            ) => Err(LlmError::Blocked(reason.to_string())),
+            _ => Err(LlmError::BadFormat(parsed.to_string())),
+        }
+    }
+    async fn post_json(&self, url: &str, body: Value) -> Result<Value, LlmError> {
+        let mut request = self.client.post(url).json(&body);
+        if let Some(key) = &self.provider.api_key {
+            if self.provider.kind != LlmProviderKind::Gemini {
+                request = request.bearer_auth(key);
+            }
+        }
+        let resp = request
+            .send()
+            .await
+            .map_err(|e| LlmError::Transport(e.to_string()))?;
         let status = resp.status();
-        let text: String = resp.text().await.unwrap();
-
+        let retry_after_secs = resp
+            .headers()
+            .get(reqwest::header::RETRY_AFTER)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.parse::<u64>().ok());
+        let text: String = resp
+            .text()
+            .await
+            .map_err(|e| LlmError::Transport(e.to_string()))?;
         if !status.is_success() {
-            panic!("LLM API Error (Status {}): {}", status, text);
+            return Err(Self::classify_status(
+                status.as_u16(),
+                &text,
+                retry_after_secs,
+            ));
         }
-
-        let parsed: Value = serde_json::from_str(&text).unwrap();
-        if let Some(content) = parsed["candidates"][0]["content"]["parts"][0]["text"].as_str() {
-            return content.to_string();
+        serde_json::from_str(&text).map_err(|e| LlmError::BadFormat(format!("{}: {}", e, text)))
+    }
+    fn classify_status(status: u16, body: &str, retry_after_secs: Option<u64>) -> LlmError {
+        let parsed: Value = serde_json::from_str(body).unwrap_or(Value::Null);
+        let message = parsed["error"]["message"]
+            .as_str()
+            .or_else(|| parsed["error"].as_str())
+            .unwrap_or(body)
+            .to_string();
+        match status {
+            429 => {
+                let lowered = message.to_lowercase();
+                if lowered.contains("per day") || lowered.contains("billing") {
+                    return LlmError::Quota(message);
+                }
+                let retry_after_secs = parsed["error"]["details"]
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .find_map(|d| d["retryDelay"].as_str())
+                    .and_then(|d| d.trim_end_matches('s').parse::<f64>().ok())
+                    .map(|secs| secs.ceil() as u64)
+                    .or(retry_after_secs);
+                LlmError::RateLimited {
+                    message,
+                    retry_after_secs,
+                }
+            }
+            500..=599 => LlmError::Transport(format!("status {}: {}", status, message)),
+            _ => LlmError::Api { status, message },
         }
-        panic!("LLM response bad format: {:?}", parsed)
     }
-    fn parse_evaluation(content: &str) -> Evaluation {
+}
+#[derive(Clone)]
+pub struct LlmClient {
+    transcription: ProviderChain,
+    summarization: ProviderChain,
+    novelization: ProviderChain,
+    evaluation: ProviderChain,
+    prompts: Prompts,
+}
+impl LlmClient {
+    pub fn new(
+        routing: LlmRouting,
+        cache: Option<Arc<LlmCache>>,
+        prompts: Prompts,
+    ) -> Result<Self, LlmError> {
+        let mut registry: HashMap<String, Arc<RateLimitedProvider>> = HashMap::new();
+        let mut chain = |role: &'static str, providers: Vec<LlmProvider>| {
+            let mut steps = Vec::new();
+            for provider in providers {
+                let step = match registry.get(&provider.name) {
+                    Some(step) => step.clone(),
+                    None => {
+                        let name = provider.name.clone();
+                        let step = Arc::new(RateLimitedProvider::new(provider)?);
+                        registry.insert(name, step.clone());
+                        step
+                    }
+                };
+                steps.push(step);
+            }
+            Ok::<_, LlmError>(ProviderChain::new(role, steps, cache.clone()))
+        };
+        Ok(Self {
+            transcription: chain("transcription", routing.transcription)?,
+            summarization: chain("summarization", routing.summarization)?,
+            novelization: chain("novelization", routing.novelization)?,
+            evaluation: chain("evaluation", routing.evaluation)?,
+            prompts,
+        })
+    }
+    async fn generate_evaluation(
+        &self,
+        template: PromptTemplate<'_>,
+        inputs: &[&str],
+        prompt: String,
+    ) -> Result<Evaluation, LlmError> {
+        let schema = json!({
+            "type": "object",
+            "properties": {
+                "faithfulness_score": { "type": "integer" },
+                "quality_score": { "type": "integer" },
+                "reasoning": { "type": "string" }
+            },
+            "required": ["faithfulness_score", "quality_score", "reasoning"],
+            "additionalProperties": false
+        });
+        let content: String = self
+            .evaluation
+            .send(&LlmRequest::Generate {
+                template,
+                inputs,
+                prompt: &prompt,
+                schema: Some(ResponseSchema {
+                    name: "evaluation",
+                    schema: &schema,
+                }),
+            })
+            .await?;
+        Self::parse_evaluation(&content)
+    }
+    fn parse_evaluation(content: &str) -> Result<Evaluation, LlmError> {
         let cleaned = content
             .trim_start_matches("```json")
             .trim_end_matches("```")
             .trim();
-        if cleaned.starts_with('{') {
-            return serde_json::from_str(cleaned).unwrap();
-        }
-        let start = cleaned.find('{').unwrap();
-        let end = cleaned.rfind('}').unwrap();
-        serde_json::from_str(&cleaned[start..=end]).unwrap()
+        let json = match (cleaned.find('{'), cleaned.rfind('}')) {
+            (Some(start), Some(end)) if start < end => &cleaned[start..=end],
+            _ => {
+                return Err(LlmError::BadFormat(format!(
+                    "no JSON object in: {}",
+                    content
+                )))
+            }
+        };
+        serde_json::from_str(json).map_err(|e| LlmError::BadFormat(format!("{}: {}", e, json)))
     }
 }
 #[async_trait::async_trait]
-impl Novelizer for GeminiClient {
-    async fn generate_chapter(&self, summary: &str, context: &str) -> String {
+impl Novelizer for LlmClient {
+    async fn generate_chapter(&self, summary: &str, context: &str) -> Result<String, LlmError> {
         let template: &String = &self.prompts.novelizer.template;
         let prompt: String = template
             .replace("{novel_so_far}", context)
             .replace("{today_summary}", summary);
-        self.generate_content(&prompt).await
+        self.novelization
+            .send(&LlmRequest::Generate {
+                template: PromptTemplate {
+                    name: "novelizer.template",
+                    text: template,
+                },
+                inputs: &[context, summary],
+                prompt: &prompt,
+                schema: None,
+            })
+            .await
     }
 }
 #[async_trait::async_trait]
-impl crate::domain::ContentGenerator for GeminiClient {
-    async fn generate_content(&self, prompt: &str) -> String {
-        self.generate_content(prompt).await
-    }
-    async fn transcribe(&self, file_path: &str) -> String {
-        if self.model.starts_with("ollama:") {
-            let output_dir = "data/transcripts";
-            std::fs::create_dir_all(output_dir).unwrap();
-            let status = Command::new("whisper")
-                .arg(file_path)
-                .arg("--output_dir")
-                .arg(output_dir)
-                .arg("--output_format")
-                .arg("txt")
-                .arg("--task")
-                .arg("transcribe")
-                .status()
-                .unwrap();
-            if !status.success() {
-                panic!("whisper transcription failed with status {}", status);
-            }
-            let stem = std::path::Path::new(file_path)
-                .file_stem()
-                .unwrap()
-                .to_str()
-                .unwrap();
-            let transcript_path = format!("{}/{}.txt", output_dir, stem);
-            return std::fs::read_to_string(transcript_path).unwrap();
-        }
-        let audio_data: Vec<u8> = std::fs::read(file_path).unwrap();
-        let ext: &str = std::path::Path::new(file_path)
-            .extension()
-            .unwrap()
-            .to_str()
-            .unwrap();
-        let mime_type: &str = match ext {
-            "wav" => "audio/wav",
-            "flac" => "audio/flac",
-            "mp3" => "audio/mp3",
-            _ => "audio/wav",
-        };
-        self.transcribe_audio(&audio_data, mime_type).await
+impl crate::domain::ContentGenerator for LlmClient {
+    async fn generate_content(&self, prompt: &str) -> Result<String, LlmError> {
+        self.summarization
+            .send(&LlmRequest::Generate {
+                template: PromptTemplate {
+                    name: "content",
+                    text: "",
+                },
+                inputs: &[prompt],
+                prompt,
+                schema: None,
+            })
+            .await
+    }
+    async fn transcribe(&self, file_path: &str) -> Result<String, LlmError> {
+        self.transcription
+            .send(&LlmRequest::Transcribe {
+                file_path,
+                prompt: &self.prompts.transcription,
+            })
+            .await
     }
 }
 #[async_trait::async_trait]
-impl Curator for GeminiClient {
-    async fn evaluate(&self, summary: &str, novel: &str) -> Evaluation {
+impl Curator for LlmClient {
+    async fn evaluate(&self, summary: &str, novel: &str) -> Result<Evaluation, LlmError> {
         let template: &String = &self.prompts.curator.evaluate;
         let prompt: String = template
             .replace("{summary}", summary)
             .replace("{novel}", novel);
-        let content: String = if self.model.starts_with("ollama:") {
-            self.generate_content(&format!(
-                "Return only valid JSON with keys faithfulness_score, quality_score, reasoning.\n{}",
-                prompt
-            ))
-            .await
-        } else {
-            self.generate_content(&prompt).await
-        };
-        Self::parse_evaluation(&content)
+        self.generate_evaluation(
+            PromptTemplate {
+                name: "curator.evaluate",
+                text: template,
+            },
+            &[summary, novel],
+            prompt,
+        )
+        .await
     }
     async fn verify_summary(
         &self,
         summary: &str,
         transcript: &str,
         activities: &str,
-    ) -> Evaluation {
-        let prompt: String = self
-            .prompts
-            .summary_verification
+    ) -> Result<Evaluation, LlmError> {
+        let template: &String = &self.prompts.summary_verification;
+        let prompt: String = template
             .replace("{summary}", summary)
             .replace("{transcript}", transcript)
             .replace("{activities}", activities);
-        let content: String = if self.model.starts_with("ollama:") {
-            self.generate_content(&format!(
-                "Return only valid JSON with keys faithfulness_score, quality_score, reasoning.\n{}",
-                prompt
-            ))
-            .await
-        } else {
-            self.generate_content(&prompt).await
-        };
-        Self::parse_evaluation(&content)
+        self.generate_evaluation(
+            PromptTemplate {
+                name: "summary_verification",
+                text: template,
+            },
+            &[summary, transcript, activities],
+            prompt,
+        )
+        .await
     }
-    async fn summarize_session(&self, transcript: &str, activities: &str) -> String {
-        let prompt: String = self
-            .prompts
-            .curator
-            .session_summary
+    async fn summarize_session(
+        &self,
+        transcript: &str,
+        activities: &str,
+        variant: Option<&str>,
+    ) -> Result<String, LlmError> {
+        let curator = &self.prompts.curator;
+        let (name, template) = match variant {
+            Some(name) => match curator.session_summary_variants.get(name) {
+                Some(template) => (
+                    format!("curator.session_summary_variants.{}", name),
+                    template,
+                ),
+                None => {
+                    tracing::warn!(
+                        "Summary prompt variant '{}' not found; using the default",
+                        name
+                    );
+                    (
+                        "curator.session_summary".to_string(),
+                        &curator.session_summary,
+                    )
+                }
+            },
+            None => (
+                "curator.session_summary".to_string(),
+                &curator.session_summary,
+            ),
+        };
+        let prompt: String = template
             .replace("{transcript}", transcript)
             .replace("{activity_context}", activities);
-        self.generate_content(&prompt).await
+        self.summarization
+            .send(&LlmRequest::Generate {
+                template: PromptTemplate {
+                    name: &name,
+                    text: template,
+                },
+                inputs: &[transcript, activities],
+                prompt: &prompt,
+                schema: None,
+            })
+            .await
     }
 }
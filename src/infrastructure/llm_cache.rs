@@ -0,0 +1,205 @@
+use crate::domain::{CacheMode, LlmCacheConfig, LlmProvider};
+use crate::infrastructure::llm::LlmRequest;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+use tracing::{debug, warn};
+#[derive(Debug, Clone)]
+pub struct Fingerprint {
+    prompt_hash: String,
+    input_hash: String,
+}
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CacheKey {
+    pub provider: String,
+    pub model: String,
+    pub prompt_hash: String,
+    pub input_hash: String,
+}
+impl CacheKey {
+    fn id(&self) -> String {
+        sha256_hex(
+            format!(
+                "{}\n{}\n{}\n{}",
+                self.provider, self.model, self.prompt_hash, self.input_hash
+            )
+            .as_bytes(),
+        )
+    }
+}
+#[derive(Debug, Serialize, Deserialize)]
+struct CacheEntry {
+    #[serde(flatten)]
+    key: CacheKey,
+    created_at: DateTime<Utc>,
+    response: String,
+}
+pub struct LlmCache {
+    config: LlmCacheConfig,
+    mode: CacheMode,
+    size: AtomicU64,
+}
+impl LlmCache {
+    pub fn open(config: &LlmCacheConfig, mode: CacheMode) -> Option<Arc<Self>> {
+        if !config.enabled || mode == CacheMode::Bypass {
+            return None;
+        }
+        let cache = Self {
+            config: config.clone(),
+            mode,
+            size: AtomicU64::new(0),
+        };
+        cache.evict();
+        Some(Arc::new(cache))
+    }
+    pub async fn fingerprint(&self, request: &LlmRequest<'_>) -> Option<Fingerprint> {
+        match *request {
+            LlmRequest::Generate {
+                template,
+                inputs,
+                schema,
+                ..
+            } => {
+                let mut hasher = Sha256::new();
+                hasher.update(format!(
+                    "generate schema={}\n",
+                    schema.map(|s| s.schema.to_string()).unwrap_or_default()
+                ));
+                for input in inputs {
+                    hasher.update(format!("{}\n", input.len()));
+                    hasher.update(input);
+                }
+                Some(Fingerprint {
+                    prompt_hash: sha256_hex(
+                        format!("{}\n{}", template.name, template.text).as_bytes(),
+                    ),
+                    input_hash: format!("{:x}", hasher.finalize()),
+                })
+            }
+            LlmRequest::Transcribe { file_path, prompt } => {
+                let path = PathBuf::from(file_path);
+                let hashed = tokio::task::spawn_blocking(move || -> std::io::Result<String> {
+                    let mut hasher = Sha256::new();
+                    std::io::copy(&mut fs::File::open(path)?, &mut hasher)?;
+                    Ok(format!("{:x}", hasher.finalize()))
+                })
+                .await
+                .unwrap_or_else(|e| Err(std::io::Error::other(e)));
+                match hashed {
+                    Ok(input_hash) => Some(Fingerprint {
+                        prompt_hash: sha256_hex(prompt.as_bytes()),
+                        input_hash,
+                    }),
+                    Err(e) => {
+                        warn!("Not caching transcription of {}: {}", file_path, e);
+                        None
+                    }
+                }
+            }
+        }
+    }
+    pub fn key(&self, provider: &LlmProvider, fingerprint: &Fingerprint) -> CacheKey {
+        CacheKey {
+            provider: provider.name.clone(),
+            model: provider.model.clone(),
+            prompt_hash: fingerprint.prompt_hash.clone(),
+            input_hash: fingerprint.input_hash.clone(),
+        }
+    }
+    pub fn get(&self, key: &CacheKey) -> Option<String> {
+        if self.mode == CacheMode::Refresh {
+            return None;
+        }
+        let path = self.path(&key.id());
+        let entry: CacheEntry = serde_json::from_str(&fs::read_to_string(&path).ok()?).ok()?;
+        let age = Utc::now() - entry.created_at;
+        if age.num_seconds() > self.config.ttl_secs as i64 {
+            debug!("Cache entry {} expired", path.display());
+            let len = fs::metadata(&path).map_or(0, |meta| meta.len());
+            if fs::remove_file(&path).is_ok() {
+                self.resize(0, len);
+            }
+            return None;
+        }
+        Some(entry.response)
+    }
+    pub fn put(&self, key: &CacheKey, response: &str) {
+        let path = self.path(&key.id());
+        let entry = CacheEntry {
+            key: key.clone(),
+            created_at: Utc::now(),
+            response: response.to_string(),
+        };
+        let bytes = match serde_json::to_vec(&entry) {
+            Ok(bytes) => bytes,
+            Err(e) => {
+                warn!(
+                    "Failed to serialize LLM cache entry {}: {}",
+                    path.display(),
+                    e
+                );
+                return;
+            }
+        };
+        let replaced = fs::metadata(&path).map_or(0, |meta| meta.len());
+        let written = path
+            .parent()
+            .map_or(Ok(()), fs::create_dir_all)
+            .and_then(|_| fs::write(&path, &bytes));
+        if let Err(e) = written {
+            warn!("Failed to write LLM cache entry {}: {}", path.display(), e);
+            return;
+        }
+        if self.resize(bytes.len() as u64, replaced) > self.config.max_bytes {
+            self.evict();
+        }
+    }
+    fn resize(&self, added: u64, removed: u64) -> u64 {
+        let apply = |size: u64| size.saturating_sub(removed) + added;
+        let previous = self
+            .size
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |size| Some(apply(size)))
+            .unwrap_or_else(|size| size);
+        apply(previous)
+    }
+    fn path(&self, id: &str) -> PathBuf {
+        self.config.dir.join(&id[..2]).join(format!("{}.json", id))
+    }
+    fn evict(&self) {
+        let ttl = Duration::from_secs(self.config.ttl_secs);
+        let now = SystemTime::now();
+        let mut entries: Vec<(PathBuf, u64, SystemTime)> = fs::read_dir(&self.config.dir)
+            .into_iter()
+            .flatten()
+            .flatten()
+            .filter_map(|shard| fs::read_dir(shard.path()).ok())
+            .flatten()
+            .flatten()
+            .filter_map(|file| {
+                let meta = file.metadata().ok()?;
+                Some((file.path(), meta.len(), meta.modified().ok()?))
+            })
+            .collect();
+        entries.sort_by_key(|(_, _, modified)| *modified);
+        let mut total: u64 = entries.iter().map(|(_, len, _)| len).sum();
+        for (path, len, modified) in entries {
+            let expired = now.duration_since(modified).unwrap_or_default() > ttl;
+            if !expired && total <= self.config.max_bytes {
+                break;
+            }
+            if fs::remove_file(&path).is_ok() {
+                total = total.saturating_sub(len);
+                debug!("Evicted LLM cache entry {}", path.display());
+            }
+        }
+        self.size.store(total, Ordering::SeqCst);
+    }
+}
+fn sha256_hex(bytes: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(bytes))
+}
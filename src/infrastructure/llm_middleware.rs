@@ -0,0 +1,187 @@
+use crate::domain::{LlmError, LlmProvider};
+use crate::infrastructure::llm::{LlmRequest, ProviderClient};
+use crate::infrastructure::llm_cache::LlmCache;
+use std::hash::{BuildHasher, Hasher};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
+use tokio::time::sleep;
+use tracing::{info, warn};
+pub struct TokenBucket {
+    capacity: f64,
+    refill_per_sec: f64,
+    state: Mutex<(f64, Instant)>,
+}
+impl TokenBucket {
+    pub fn new(requests_per_minute: u32, burst: u32) -> Self {
+        let capacity = f64::from(burst.max(1));
+        Self {
+            capacity,
+            refill_per_sec: f64::from(requests_per_minute) / 60.0,
+            state: Mutex::new((capacity, Instant::now())),
+        }
+    }
+    pub async fn acquire(&self) {
+        loop {
+            let wait = {
+                let mut state = self.state.lock().await;
+                let now = Instant::now();
+                let (tokens, last) = *state;
+                let tokens = (tokens
+                    + now.duration_since(last).as_secs_f64() * self.refill_per_sec)
+                    .min(self.capacity);
+                if tokens >= 1.0 {
+                    *state = (tokens - 1.0, now);
+                    return;
+                }
+                *state = (tokens, now);
+                Duration::from_secs_f64((1.0 - tokens) / self.refill_per_sec)
+            };
+            sleep(wait).await;
+        }
+    }
+}
+pub struct RateLimitedProvider {
+    client: ProviderClient,
+    bucket: Option<TokenBucket>,
+}
+impl RateLimitedProvider {
+    pub fn new(provider: LlmProvider) -> Result<Self, LlmError> {
+        let bucket = provider
+            .requests_per_minute
+            .map(|rpm| TokenBucket::new(rpm, provider.burst));
+        Ok(Self {
+            client: ProviderClient::new(provider)?,
+            bucket,
+        })
+    }
+    pub fn provider(&self) -> &LlmProvider {
+        self.client.provider()
+    }
+    async fn send(&self, request: &LlmRequest<'_>) -> Result<String, LlmError> {
+        if let Some(bucket) = &self.bucket {
+            bucket.acquire().await;
+        }
+        self.client.send(request).await
+    }
+}
+#[derive(Clone)]
+pub struct ProviderChain {
+    role: &'static str,
+    providers: Vec<Arc<RateLimitedProvider>>,
+    cache: Option<Arc<LlmCache>>,
+}
+impl ProviderChain {
+    pub fn new(
+        role: &'static str,
+        providers: Vec<Arc<RateLimitedProvider>>,
+        cache: Option<Arc<LlmCache>>,
+    ) -> Self {
+        Self {
+            role,
+            providers,
+            cache,
+        }
+    }
+    pub async fn send(&self, request: &LlmRequest<'_>) -> Result<String, LlmError> {
+        let cache = match &self.cache {
+            Some(cache) => cache.fingerprint(request).await.map(|f| (cache, f)),
+            None => None,
+        };
+        let keys: Vec<_> = self
+            .providers
+            .iter()
+            .map(|step| {
+                cache
+                    .as_ref()
+                    .map(|(cache, fingerprint)| cache.key(step.provider(), fingerprint))
+            })
+            .collect();
+        if let Some((cache, _)) = &cache {
+            for key in keys.iter().flatten() {
+                if let Some(text) = cache.get(key) {
+                    info!(
+                        "LLM {} served from cache ({} / {})",
+                        self.role, key.provider, key.model
+                    );
+                    return Ok(text);
+                }
+            }
+        }
+        let mut last_error = None;
+        for (index, step) in self.providers.iter().enumerate() {
+            let provider = step.provider();
+            let attempts = provider.max_retries + 1;
+            for attempt in 1..=attempts {
+                match step.send(request).await {
+                    Ok(text) => {
+                        if let (Some((cache, _)), Some(key)) = (&cache, &keys[index]) {
+                            cache.put(key, &text);
+                        }
+                        info!(
+                            "LLM {} answered by {} ({}) on attempt {}/{}{}",
+                            self.role,
+                            provider.name,
+                            provider.model,
+                            attempt,
+                            attempts,
+                            if index > 0 { " after fallback" } else { "" }
+                        );
+                        return Ok(text);
+                    }
+                    Err(e) => {
+                        let delay = Self::retry_delay(&e, attempt, attempts);
+                        warn!(
+                            "LLM {} attempt {}/{} via {} ({}) failed: {}{}",
+                            self.role,
+                            attempt,
+                            attempts,
+                            provider.name,
+                            provider.model,
+                            e,
+                            delay
+                                .map(|d| format!("; retrying in {:.1}s", d.as_secs_f64()))
+                                .unwrap_or_default()
+                        );
+                        last_error = Some(e);
+                        match delay {
+                            Some(delay) => sleep(delay).await,
+                            None => break,
+                        }
+                    }
+                }
+            }
+            if let Some(next) = self.providers.get(index + 1) {
+                warn!(
+                    "LLM {} falling back from {} to {}",
+                    self.role,
+                    provider.name,
+                    next.provider().name
+                );
+            }
+        }
+        Err(last_error.unwrap_or_else(|| {
+            LlmError::Transport(format!("no LLM provider configured for {}", self.role))
+        }))
+    }
+    fn retry_delay(error: &LlmError, attempt: u32, attempts: u32) -> Option<Duration> {
+        if attempt >= attempts
+            || !matches!(error, LlmError::RateLimited { .. } | LlmError::Transport(_))
+        {
+            return None;
+        }
+        let backoff = crate::domain::constants::LLM_RETRY_BASE_MS
+            .saturating_mul(1 << (attempt - 1).min(16))
+            .min(crate::domain::constants::LLM_RETRY_MAX_MS);
+        let jitter = std::collections::hash_map::RandomState::new()
+            .build_hasher()
+            .finish()
+            % (backoff / 2 + 1);
+        let delay = backoff / 2 + jitter;
+        match error.retry_after_secs().map(|secs| secs * 1000) {
+            Some(ms) if ms > crate::domain::constants::LLM_RETRY_MAX_MS => None,
+            Some(ms) => Some(Duration::from_millis(ms.max(delay))),
+            None => Some(Duration::from_millis(delay)),
+        }
+    }
+}
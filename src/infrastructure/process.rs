@@ -1,8 +1,11 @@
 use crate::domain::ProcessMonitor as ProcessMonitorTrait;
-use std::collections::BTreeSet;
+use crate::domain::{ProcessMatch, RecordingProfile, TriggerRule, WindowsProcessSource};
+use crate::infrastructure::windows_feed::{FeedProcess, WindowsProcessFeed};
+use std::collections::{BTreeSet, HashMap};
 use std::process::Command;
+use std::time::{Duration, Instant};
 use sysinfo::System;
-use tracing::{debug, info};
+use tracing::{debug, info, warn};
 #[cfg(windows)]
 use windows::core::PWSTR;
 #[cfg(windows)]
@@ -13,48 +16,186 @@ use windows::Win32::System::Threading::{
 use windows::Win32::UI::WindowsAndMessaging::GetWindowTextW;
 #[cfg(windows)]
 use windows::Win32::UI::WindowsAndMessaging::{GetForegroundWindow, GetWindowThreadProcessId};
+struct ProcessFacts {
+    name: String,
+    exe: String,
+    cmdline: String,
+    window_titles: Vec<String>,
+}
+impl ProcessFacts {
+    fn new(name: &str, exe: &str, cmdline: &str, window_titles: Vec<String>) -> Self {
+        Self {
+            name: name.to_lowercase(),
+            exe: exe.to_lowercase(),
+            cmdline: cmdline.to_lowercase(),
+            window_titles: window_titles.iter().map(|t| t.to_lowercase()).collect(),
+        }
+    }
+}
+fn lowercase_rule(rule: &TriggerRule) -> TriggerRule {
+    match rule {
+        TriggerRule::Process(p) => TriggerRule::Process(p.to_lowercase()),
+        TriggerRule::WindowTitle(t) => TriggerRule::WindowTitle(t.to_lowercase()),
+        TriggerRule::Cmdline(c) => TriggerRule::Cmdline(c.to_lowercase()),
+        TriggerRule::All(rules) => TriggerRule::All(rules.iter().map(lowercase_rule).collect()),
+        TriggerRule::Any(rules) => TriggerRule::Any(rules.iter().map(lowercase_rule).collect()),
+        TriggerRule::Not(rule) => TriggerRule::Not(Box::new(lowercase_rule(rule))),
+    }
+}
+fn rule_matches(rule: &TriggerRule, facts: &ProcessFacts) -> bool {
+    match rule {
+        TriggerRule::Process(p) => {
+            facts.name.contains(p.as_str()) || facts.exe.contains(p.as_str())
+        }
+        TriggerRule::WindowTitle(t) => facts.window_titles.iter().any(|w| w.contains(t.as_str())),
+        TriggerRule::Cmdline(c) => facts.cmdline.contains(c.as_str()),
+        TriggerRule::All(rules) => rules.iter().all(|r| rule_matches(r, facts)),
+        TriggerRule::Any(rules) => rules.iter().any(|r| rule_matches(r, facts)),
+        TriggerRule::Not(rule) => !rule_matches(rule, facts),
+    }
+}
+fn process_patterns(rule: &TriggerRule, out: &mut Vec<String>) -> bool {
+    match rule {
+        TriggerRule::Process(p) => {
+            out.push(p.clone());
+            true
+        }
+        TriggerRule::All(rules) | TriggerRule::Any(rules) => {
+            rules.iter().all(|r| process_patterns(r, out))
+        }
+        TriggerRule::WindowTitle(_) | TriggerRule::Cmdline(_) | TriggerRule::Not(_) => false,
+    }
+}
 pub struct ProcessMonitor {
-    targets: Vec<String>,
+    profiles: Vec<RecordingProfile>,
+    triggers: Vec<Option<TriggerRule>>,
+    needs_titles: bool,
+    needs_cmdline: bool,
+    windows_source: WindowsProcessSource,
+    windows_feed: Option<WindowsProcessFeed>,
+    feed_retry_at: Option<Instant>,
     system: System,
     last_status: bool,
     last_match: Option<String>,
 }
 impl ProcessMonitor {
-    pub fn new(targets: Vec<String>) -> Self {
+    pub fn new(profiles: Vec<RecordingProfile>, windows_source: WindowsProcessSource) -> Self {
+        let triggers: Vec<Option<TriggerRule>> = profiles
+            .iter()
+            .map(|p| {
+                let trigger = p.trigger().map(|rule| lowercase_rule(&rule));
+                if trigger.is_none() {
+                    warn!(
+                        "Profile '{}' has no processes or rule; it never matches",
+                        p.name
+                    );
+                }
+                trigger
+            })
+            .collect();
+        let needs_titles = triggers
+            .iter()
+            .flatten()
+            .any(TriggerRule::uses_window_title);
+        let needs_cmdline = triggers.iter().flatten().any(TriggerRule::uses_cmdline);
         Self {
-            targets: targets.into_iter().map(|t| t.to_lowercase()).collect(),
+            profiles,
+            triggers,
+            needs_titles,
+            needs_cmdline,
+            windows_source,
+            windows_feed: None,
+            feed_retry_at: None,
             system: System::new_all(),
             last_status: false,
             last_match: None,
         }
     }
-    fn check_processes(&self) -> Option<String> {
-        self.system.processes().values().find_map(|process| {
-            let name = process.name().to_lowercase();
-            if self.targets.iter().any(|target| name.contains(target)) {
-                return Some(format!("linux:{} (pid={})", process.name(), process.pid()));
-            }
-            process.exe().and_then(|exe_path| {
-                let exe = exe_path.to_string_lossy().to_lowercase();
-                if self.targets.iter().any(|target| exe.contains(target)) {
-                    Some(format!(
-                        "linux:{} (pid={}, exe={})",
-                        process.name(),
-                        process.pid(),
-                        exe_path.to_string_lossy()
-                    ))
-                } else {
-                    None
-                }
-            })
+    fn profile_for(&self, facts: &ProcessFacts) -> Option<usize> {
+        self.triggers.iter().position(|trigger| {
+            trigger
+                .as_ref()
+                .is_some_and(|rule| rule_matches(rule, facts))
         })
     }
+    fn check_processes(&self) -> Option<(usize, String)> {
+        let titles = self.window_titles();
+        let mut best: Option<(usize, String)> = None;
+        for (pid, process) in self.system.processes() {
+            let exe = process
+                .exe()
+                .map(|p| p.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            let facts = ProcessFacts::new(
+                process.name(),
+                &exe,
+                &process.cmd().join(" "),
+                titles.get(&pid.as_u32()).cloned().unwrap_or_default(),
+            );
+            let Some(index) = self.profile_for(&facts) else {
+                continue;
+            };
+            if best.as_ref().is_some_and(|(b, _)| *b <= index) {
+                continue;
+            }
+            let info = if exe.is_empty() {
+                format!("linux:{} (pid={})", process.name(), pid)
+            } else {
+                format!("linux:{} (pid={}, exe={})", process.name(), pid, exe)
+            };
+            best = Some((index, info));
+        }
+        best
+    }
+    #[cfg(windows)]
+    fn window_titles(&self) -> HashMap<u32, Vec<String>> {
+        let mut titles = HashMap::new();
+        if !self.needs_titles {
+            return titles;
+        }
+        if let Some((pid, title, _)) = Self::foreground_window() {
+            titles.insert(pid, vec![title]);
+        }
+        titles
+    }
+    #[cfg(not(windows))]
+    fn window_titles(&self) -> HashMap<u32, Vec<String>> {
+        let mut titles: HashMap<u32, Vec<String>> = HashMap::new();
+        if !self.needs_titles {
+            return titles;
+        }
+        let output = match Command::new(crate::domain::constants::WMCTRL_CMD)
+            .arg("-lp")
+            .output()
+        {
+            Ok(output) => output,
+            Err(e) => {
+                debug!("Window titles unavailable (wmctrl): {}", e);
+                return titles;
+            }
+        };
+        for line in String::from_utf8_lossy(&output.stdout).lines() {
+            let mut fields = line.split_whitespace();
+            let pid = fields.nth(2).and_then(|p| p.parse::<u32>().ok());
+            let title: Vec<&str> = fields.skip(1).collect();
+            if let Some(pid) = pid {
+                titles.entry(pid).or_default().push(title.join(" "));
+            }
+        }
+        titles
+    }
     fn is_wsl() -> bool {
         std::env::var("WSL_DISTRO_NAME").is_ok()
     }
-    fn normalized_windows_targets(&self) -> Vec<String> {
+    fn normalized_windows_targets(&self) -> Option<Vec<String>> {
+        let mut patterns = Vec::new();
+        for trigger in self.triggers.iter().flatten() {
+            if !process_patterns(trigger, &mut patterns) {
+                return None;
+            }
+        }
         let mut names = BTreeSet::new();
-        for target in &self.targets {
+        for target in &patterns {
             let base = target
                 .rsplit(['\\', '/'])
                 .next()
@@ -66,44 +207,112 @@ impl ProcessMonitor {
                 names.insert(base);
             }
         }
-        names.into_iter().collect()
+        Some(names.into_iter().collect())
     }
-    fn check_windows_processes(&self) -> Option<String> {
+    fn check_windows_processes(&mut self) -> Option<(usize, String)> {
         if !Self::is_wsl() {
             return None;
         }
-        let names = self.normalized_windows_targets();
-        if names.is_empty() {
+        if let Some(processes) = self.windows_feed_snapshot() {
+            let rows = processes
+                .into_iter()
+                .map(|p| (p.name, p.path, p.cmdline, p.titles))
+                .collect();
+            return self.match_windows_rows(rows, "process feed");
+        }
+        self.poll_windows_processes()
+    }
+    fn windows_feed_snapshot(&mut self) -> Option<Vec<FeedProcess>> {
+        if self.windows_source != WindowsProcessSource::Feed {
             return None;
         }
-        let joined = names.join(",");
+        let retry = Duration::from_secs(crate::domain::constants::WINDOWS_FEED_RETRY_SECS);
+        if self.windows_feed.as_ref().is_some_and(|f| !f.is_alive()) {
+            warn!(
+                "Windows process feed exited; polling until it restarts in {}s",
+                retry.as_secs()
+            );
+            self.windows_feed = None;
+            self.feed_retry_at = Some(Instant::now() + retry);
+        }
+        if self.windows_feed.is_none() {
+            if self.feed_retry_at.is_some_and(|at| Instant::now() < at) {
+                return None;
+            }
+            match WindowsProcessFeed::spawn(
+                crate::domain::constants::WINDOWS_FEED_INTERVAL_MS,
+                self.needs_cmdline,
+            ) {
+                Ok(feed) => self.windows_feed = Some(feed),
+                Err(e) => {
+                    warn!("{}; polling instead", e);
+                    self.feed_retry_at = Some(Instant::now() + retry);
+                    return None;
+                }
+            }
+        }
+        self.windows_feed.as_ref()?.snapshot()
+    }
+    fn poll_windows_processes(&self) -> Option<(usize, String)> {
+        let filter = match self.normalized_windows_targets() {
+            Some(names) if names.is_empty() => return None,
+            Some(names) => format!("-Name {} ", names.join(",")),
+            None => String::new(),
+        };
+        let cmdlines = if self.needs_cmdline {
+            "$c = @{}; Get-CimInstance Win32_Process -Property ProcessId,CommandLine -ErrorAction SilentlyContinue | ForEach-Object { $c[[int]$_.ProcessId] = $_.CommandLine -replace \"`t\", ' ' }; "
+        } else {
+            "$c = @{}; "
+        };
         let output = Command::new(crate::domain::constants::POWERSHELL_PATH)
             .args([
                 "-NoLogo",
                 "-NoProfile",
                 "-Command",
                 &format!(
-                    "Get-Process -Name {} -ErrorAction SilentlyContinue | Select-Object -ExpandProperty ProcessName",
-                    joined
+                    "{}Get-Process {}-ErrorAction SilentlyContinue | ForEach-Object {{ \"$($_.ProcessName)`t$($_.Path)`t$($c[$_.Id])`t$($_.MainWindowTitle)\" }}",
+                    cmdlines, filter
                 ),
             ])
             .output();
         let out = output.unwrap();
         let stdout = String::from_utf8_lossy(&out.stdout);
-        let first = stdout
+        let rows = stdout
             .lines()
-            .map(str::trim)
-            .find(|line| !line.is_empty())
-            .map(ToOwned::to_owned);
-        if let Some(proc_name) = &first {
-            debug!("Windows process detected via powershell.exe: {}", proc_name);
-            Some(format!("windows:{}", proc_name))
-        } else {
-            None
-        }
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| {
+                let mut fields = line.trim_end_matches('\r').splitn(4, '\t');
+                let name = fields.next().unwrap_or_default().trim().to_string();
+                let exe = fields.next().unwrap_or_default().trim().to_string();
+                let cmdline = fields.next().unwrap_or_default().trim().to_string();
+                let title = fields.next().unwrap_or_default().trim();
+                let titles = if title.is_empty() {
+                    Vec::new()
+                } else {
+                    vec![title.to_string()]
+                };
+                (name, exe, cmdline, titles)
+            })
+            .collect();
+        self.match_windows_rows(rows, "powershell.exe")
+    }
+    fn match_windows_rows(
+        &self,
+        rows: Vec<(String, String, String, Vec<String>)>,
+        via: &str,
+    ) -> Option<(usize, String)> {
+        let (index, proc_name) = rows
+            .into_iter()
+            .filter_map(|(name, exe, cmdline, titles)| {
+                let facts = ProcessFacts::new(&name, &exe, &cmdline, titles);
+                self.profile_for(&facts).map(|index| (index, name))
+            })
+            .min_by_key(|(index, _)| *index)?;
+        debug!("Windows process detected via {}: {}", via, proc_name);
+        Some((index, format!("windows:{}", proc_name)))
     }
     #[cfg(windows)]
-    fn check_native_windows_processes(&self) -> Option<String> {
+    fn foreground_window() -> Option<(u32, String, String)> {
         unsafe {
             let hwnd = GetForegroundWindow();
             if hwnd.0 == 0 {
@@ -111,6 +320,9 @@ impl ProcessMonitor {
             }
             let mut process_id = 0u32;
             GetWindowThreadProcessId(hwnd, Some(&mut process_id));
+            let mut title_buf = [0u16; 512];
+            let title_len = GetWindowTextW(hwnd, &mut title_buf);
+            let title = String::from_utf16_lossy(&title_buf[..title_len.max(0) as usize]);
             let handle = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, false, process_id).ok()?;
             let mut buf = [0u16; 512];
             let mut len = buf.len() as u32;
@@ -125,30 +337,44 @@ impl ProcessMonitor {
                 return None;
             }
             let path = String::from_utf16_lossy(&buf[..len as usize]);
-            let path_obj = std::path::Path::new(&path);
-            let exe_name = path_obj
-                .file_name()
-                .and_then(|n| n.to_str())?
-                .to_lowercase();
-            if self.targets.iter().any(|target| exe_name.contains(target)) {
-                info!("Target process detected (Native Windows): {}", exe_name);
-                return Some(format!("windows-native:{}", exe_name));
-            }
-            None
+            Some((process_id, title, path))
         }
     }
+    #[cfg(windows)]
+    fn check_native_windows_processes(&self) -> Option<(usize, String)> {
+        let (pid, title, path) = Self::foreground_window()?;
+        let exe_name = std::path::Path::new(&path)
+            .file_name()
+            .and_then(|n| n.to_str())?
+            .to_lowercase();
+        let cmdline = self
+            .system
+            .process(sysinfo::Pid::from_u32(pid))
+            .map(|p| p.cmd().join(" "))
+            .unwrap_or_default();
+        let facts = ProcessFacts::new(&exe_name, &path, &cmdline, vec![title.clone()]);
+        let index = self.profile_for(&facts)?;
+        info!(
+            "Target process detected (Native Windows): {} ({:?})",
+            exe_name, title
+        );
+        Some((index, format!("windows-native:{}", exe_name)))
+    }
     #[cfg(not(windows))]
-    fn check_native_windows_processes(&self) -> Option<String> {
+    fn check_native_windows_processes(&self) -> Option<(usize, String)> {
         None
     }
 }
 impl ProcessMonitorTrait for ProcessMonitor {
-    fn is_running(&mut self) -> bool {
+    fn detect(&mut self) -> Option<ProcessMatch> {
         self.system.refresh_processes();
-        let match_info = self
+        let found = self
             .check_processes()
             .or_else(|| self.check_windows_processes())
             .or_else(|| self.check_native_windows_processes());
+        let match_info = found
+            .as_ref()
+            .map(|(index, info)| format!("{} [profile={}]", info, self.profiles[*index].name));
         let current_status = match_info.is_some();
         if current_status != self.last_status {
             self.last_status = current_status;
@@ -182,6 +408,9 @@ impl ProcessMonitorTrait for ProcessMonitor {
                 self.last_match = Some(matched);
             }
         }
-        current_status
+        found.map(|(index, process)| ProcessMatch {
+            profile: self.profiles[index].clone(),
+            process,
+        })
     }
 }
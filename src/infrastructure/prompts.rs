@@ -1,11 +1,14 @@
 use anyhow::Context;
 use serde::Deserialize;
+use std::collections::HashMap;
 use std::fs::File;
 use std::path::Path;
 #[derive(Debug, Deserialize, Clone)]
 pub struct CuratorPrompts {
     pub evaluate: String,
     pub session_summary: String,
+    #[serde(default)]
+    pub session_summary_variants: HashMap<String, String>,
 }
 #[derive(Debug, Deserialize, Clone)]
 pub struct NovelizerPrompts {
@@ -1,5 +1,11 @@
+use crate::domain::{
+    AudioFormat, AudioSource, CaptureBackend, GateMode, LlmCacheConfig, LlmProvider,
+    LlmProviderKind, LlmRouting, RecordingConfig, RecordingProfile, RetryPolicy, TaskConcurrency,
+    WindowsProcessSource,
+};
 use config::{Config, Environment, File};
 use serde::Deserialize;
+use std::collections::HashMap;
 use std::env;
 pub const DEFAULT_GEMINI_MODEL: &str = "gemini-3-flash-preview";
 use std::path::PathBuf;
@@ -8,9 +14,61 @@ pub struct GeminiSettings {
     pub model: String,
 }
 #[derive(Debug, Deserialize, Clone)]
+pub struct LlmProviderSettings {
+    pub kind: LlmProviderKind,
+    #[serde(default)]
+    pub model: String,
+    pub base_url: Option<String>,
+    pub api_key_env: Option<String>,
+    pub timeout_secs: Option<u64>,
+    pub requests_per_minute: Option<u32>,
+    pub burst: Option<u32>,
+    pub max_retries: Option<u32>,
+    pub inline_max_mb: Option<u64>,
+    pub upload_base_url: Option<String>,
+}
+#[derive(Debug, Deserialize, Clone)]
+#[serde(untagged)]
+pub enum LlmRoute {
+    Single(String),
+    Chain(Vec<String>),
+}
+impl LlmRoute {
+    fn names(&self) -> Vec<String> {
+        match self {
+            LlmRoute::Single(names) => names.split(',').map(|n| n.trim().to_string()).collect(),
+            LlmRoute::Chain(names) => names.clone(),
+        }
+    }
+}
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct LlmRouteSettings {
+    pub transcription: Option<LlmRoute>,
+    pub summarization: Option<LlmRoute>,
+    pub novelization: Option<LlmRoute>,
+    pub evaluation: Option<LlmRoute>,
+}
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct LlmCacheSettings {
+    pub enabled: Option<bool>,
+    pub dir: Option<String>,
+    pub ttl_days: Option<u64>,
+    pub max_size_mb: Option<u64>,
+}
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct LlmSettings {
+    #[serde(default)]
+    pub providers: HashMap<String, LlmProviderSettings>,
+    #[serde(default)]
+    pub routes: LlmRouteSettings,
+    #[serde(default)]
+    pub cache: LlmCacheSettings,
+}
+#[derive(Debug, Deserialize, Clone)]
 pub struct ProcessSettings {
     pub names: String,
     pub check_interval: u64,
+    pub windows_source: WindowsProcessSource,
 }
 #[derive(Debug, Deserialize, Clone)]
 pub struct PathSettings {
@@ -20,7 +78,18 @@ pub struct PathSettings {
 #[derive(Debug, Deserialize, Clone)]
 pub struct AudioSettings {
     pub device_name: Option<String>,
+    #[serde(default)]
+    pub sources: Vec<AudioSource>,
+    pub backend: CaptureBackend,
+    pub fixture_path: Option<String>,
+    pub synthetic_pattern: String,
+    pub format: AudioFormat,
     pub silence_threshold: f32,
+    pub vad_hangover_ms: u64,
+    pub vad_pre_roll_ms: u64,
+    pub vad_mode: GateMode,
+    pub max_duration_minutes: u64,
+    pub pre_roll_secs: u64,
 }
 #[derive(Debug, Deserialize, Clone)]
 pub struct TriggerSettings {
@@ -29,36 +98,100 @@ pub struct TriggerSettings {
     pub min_recording_secs: u64,
 }
 #[derive(Debug, Deserialize, Clone)]
+pub struct TaskSettings {
+    pub max_attempts: u32,
+    pub retry_base_secs: u64,
+    pub retry_max_secs: u64,
+    #[serde(default)]
+    pub concurrency: HashMap<String, usize>,
+    pub llm_concurrency: usize,
+}
+#[derive(Debug, Deserialize, Clone)]
+pub struct ControlSettings {
+    pub addr: String,
+}
+#[derive(Debug, Deserialize, Clone)]
 pub struct RawSettings {
     pub process: ProcessSettings,
     pub paths: PathSettings,
     pub audio: AudioSettings,
     pub trigger: TriggerSettings,
+    pub control: ControlSettings,
+    pub tasks: TaskSettings,
     pub gemini: Option<GeminiSettings>,
+    #[serde(default)]
+    pub llm: LlmSettings,
+    #[serde(default)]
+    pub profiles: Vec<RecordingProfile>,
 }
 #[derive(Clone, Debug)]
 pub struct AudioRecordingSettings {
     pub recording_dir: PathBuf,
     pub audio_device: Option<String>,
+    pub audio_sources: Vec<AudioSource>,
+    pub audio_backend: CaptureBackend,
+    pub fixture_path: Option<PathBuf>,
+    pub synthetic_pattern: String,
+    pub audio_format: AudioFormat,
     pub silence_threshold: f32,
+    pub vad_hangover_ms: u64,
+    pub vad_pre_roll_ms: u64,
+    pub vad_mode: GateMode,
+    pub max_duration_minutes: u64,
+    pub pre_roll_secs: u64,
     pub sample_rate: u32,
     pub channels: u16,
 }
+impl AudioRecordingSettings {
+    pub fn recording_config(&self) -> RecordingConfig {
+        RecordingConfig {
+            sample_rate: self.sample_rate,
+            channels: self.channels,
+            format: self.audio_format,
+            sources: Settings::resolve_sources(&self.audio_device, &self.audio_sources),
+            backend: self.audio_backend,
+            fixture_path: self.fixture_path.clone(),
+            synthetic_pattern: self.synthetic_pattern.clone(),
+            silence_threshold: self.silence_threshold,
+            vad_hangover_ms: self.vad_hangover_ms,
+            vad_pre_roll_ms: self.vad_pre_roll_ms,
+            vad_mode: self.vad_mode,
+            max_segment_secs: self.max_duration_minutes * 60,
+            pre_roll_secs: self.pre_roll_secs,
+            session_id: None,
+        }
+    }
+}
 #[derive(Clone, Debug)]
 pub struct Settings {
-    pub google_api_key: String,
-    pub gemini_model: String,
+    pub llm: LlmRouting,
+    pub llm_cache: LlmCacheConfig,
     pub supabase_url: String,
     pub supabase_service_role_key: String,
     pub check_interval: u64,
     pub process_names: Vec<String>,
+    pub windows_process_source: WindowsProcessSource,
+    pub profiles: Vec<RecordingProfile>,
     pub recording_dir: PathBuf,
     pub db_path: PathBuf,
     pub audio_device: Option<String>,
+    pub audio_sources: Vec<AudioSource>,
+    pub audio_backend: CaptureBackend,
+    pub fixture_path: Option<PathBuf>,
+    pub synthetic_pattern: String,
+    pub audio_format: AudioFormat,
     pub silence_threshold: f32,
+    pub vad_hangover_ms: u64,
+    pub vad_pre_roll_ms: u64,
+    pub vad_mode: GateMode,
+    pub max_duration_minutes: u64,
+    pub pre_roll_secs: u64,
     pub start_debounce_secs: u64,
     pub stop_grace_secs: u64,
     pub min_recording_secs: u64,
+    pub control_addr: String,
+    pub task_retry: RetryPolicy,
+    pub task_concurrency: TaskConcurrency,
 }
 impl Settings {
     fn load_raw() -> Result<RawSettings, anyhow::Error> {
@@ -71,12 +204,45 @@ impl Settings {
                 "process.names",
                 crate::domain::constants::DEFAULT_PROCESS_NAMES,
             )?
+            .set_default(
+                "process.windows_source",
+                crate::domain::constants::DEFAULT_WINDOWS_PROCESS_SOURCE,
+            )?
             .set_default("paths.recording_dir", crate::domain::constants::APP_DIRS[0])?
             .set_default("paths.db_path", crate::domain::constants::DEFAULT_DB_PATH)?
             .set_default(
                 "audio.silence_threshold",
                 crate::domain::constants::DEFAULT_SILENCE_THRESHOLD,
             )?
+            .set_default(
+                "audio.vad_hangover_ms",
+                crate::domain::constants::DEFAULT_VAD_HANGOVER_MS,
+            )?
+            .set_default(
+                "audio.vad_pre_roll_ms",
+                crate::domain::constants::DEFAULT_VAD_PRE_ROLL_MS,
+            )?
+            .set_default("audio.vad_mode", crate::domain::constants::DEFAULT_VAD_MODE)?
+            .set_default(
+                "audio.format",
+                crate::domain::constants::DEFAULT_AUDIO_FORMAT,
+            )?
+            .set_default(
+                "audio.max_duration_minutes",
+                crate::domain::constants::DEFAULT_MAX_DURATION_MINUTES,
+            )?
+            .set_default(
+                "audio.pre_roll_secs",
+                crate::domain::constants::DEFAULT_PRE_ROLL_SECS,
+            )?
+            .set_default(
+                "audio.backend",
+                crate::domain::constants::DEFAULT_CAPTURE_BACKEND,
+            )?
+            .set_default(
+                "audio.synthetic_pattern",
+                crate::domain::constants::DEFAULT_SYNTHETIC_PATTERN,
+            )?
             .set_default(
                 "trigger.start_debounce_secs",
                 crate::domain::constants::START_DEBOUNCE_SECS_DEFAULT,
@@ -89,6 +255,26 @@ impl Settings {
                 "trigger.min_recording_secs",
                 crate::domain::constants::MIN_RECORDING_SECS_DEFAULT,
             )?
+            .set_default(
+                "control.addr",
+                crate::domain::constants::DEFAULT_CONTROL_ADDR,
+            )?
+            .set_default(
+                "tasks.max_attempts",
+                crate::domain::constants::DEFAULT_TASK_MAX_ATTEMPTS,
+            )?
+            .set_default(
+                "tasks.retry_base_secs",
+                crate::domain::constants::DEFAULT_TASK_RETRY_BASE_SECS,
+            )?
+            .set_default(
+                "tasks.retry_max_secs",
+                crate::domain::constants::DEFAULT_TASK_RETRY_MAX_SECS,
+            )?
+            .set_default(
+                "tasks.llm_concurrency",
+                crate::domain::constants::DEFAULT_LLM_CONCURRENCY as u64,
+            )?
             .add_source(File::with_name(crate::domain::constants::CONFIG_PATH).required(false))
             .add_source(Environment::default().separator("__"))
             .build()?;
@@ -97,21 +283,14 @@ impl Settings {
     pub fn new() -> Result<Self, anyhow::Error> {
         let _ = dotenvy::dotenv(); // Ignore error if .env is missing
         let raw: RawSettings = Self::load_raw()?;
-        let google_api_key = env::var("GOOGLE_API_KEY")
-            .map_err(|_| anyhow::anyhow!("GOOGLE_API_KEY must be set in environment or .env"))?;
-        let gemini_model = env::var("GEMINI_MODEL").unwrap_or_else(|_| {
-            raw.gemini
-                .as_ref()
-                .map(|g| g.model.clone())
-                .unwrap_or_else(|| crate::domain::constants::DEFAULT_GEMINI_MODEL.to_string())
-        });
+        let llm = Self::resolve_llm(&raw, true)?;
         let supabase_url =
             env::var("SUPABASE_URL").map_err(|_| anyhow::anyhow!("SUPABASE_URL must be set"))?;
         let supabase_service_role_key = env::var("SUPABASE_SERVICE_ROLE_KEY")
             .map_err(|_| anyhow::anyhow!("SUPABASE_SERVICE_ROLE_KEY must be set"))?;
         Ok(Self {
-            google_api_key,
-            gemini_model,
+            llm,
+            llm_cache: Self::resolve_llm_cache(&raw),
             supabase_url,
             supabase_service_role_key,
             check_interval: raw.process.check_interval,
@@ -121,24 +300,50 @@ impl Settings {
                 .split(',')
                 .map(|s| s.trim().to_string())
                 .collect(),
+            windows_process_source: raw.process.windows_source,
+            profiles: Self::resolve_profiles(&raw),
             recording_dir: Self::translate_path(raw.paths.recording_dir),
             db_path: Self::translate_path(raw.paths.db_path),
             audio_device: raw.audio.device_name,
+            audio_sources: raw.audio.sources,
+            audio_backend: raw.audio.backend,
+            fixture_path: raw.audio.fixture_path.map(Self::translate_path),
+            synthetic_pattern: raw.audio.synthetic_pattern,
+            audio_format: raw.audio.format,
             silence_threshold: raw.audio.silence_threshold,
+            vad_hangover_ms: raw.audio.vad_hangover_ms,
+            vad_pre_roll_ms: raw.audio.vad_pre_roll_ms,
+            vad_mode: raw.audio.vad_mode,
+            max_duration_minutes: raw.audio.max_duration_minutes,
+            pre_roll_secs: raw.audio.pre_roll_secs,
             start_debounce_secs: raw.trigger.start_debounce_secs,
             stop_grace_secs: raw.trigger.stop_grace_secs,
             min_recording_secs: raw.trigger.min_recording_secs,
+            control_addr: raw.control.addr,
+            task_retry: RetryPolicy {
+                max_attempts: raw.tasks.max_attempts,
+                base_delay_secs: raw.tasks.retry_base_secs,
+                max_delay_secs: raw.tasks.retry_max_secs,
+            },
+            task_concurrency: TaskConcurrency {
+                per_type: raw
+                    .tasks
+                    .concurrency
+                    .into_iter()
+                    .map(|(task_type, limit)| (task_type, limit.max(1)))
+                    .collect(),
+                llm_calls: raw.tasks.llm_concurrency.max(1),
+            },
         })
     }
     pub fn new_allow_missing_gemini() -> Result<Self, anyhow::Error> {
         let raw: RawSettings = Self::load_raw()?;
-        let google_api_key = env::var("GOOGLE_API_KEY").unwrap_or_default();
-        let gemini_model = env::var("GEMINI_MODEL").unwrap_or_default();
+        let llm = Self::resolve_llm(&raw, false)?;
         let supabase_url = env::var("SUPABASE_URL").unwrap_or_default();
         let supabase_service_role_key = env::var("SUPABASE_SERVICE_ROLE_KEY").unwrap_or_default();
         Ok(Self {
-            google_api_key,
-            gemini_model,
+            llm,
+            llm_cache: Self::resolve_llm_cache(&raw),
             supabase_url,
             supabase_service_role_key,
             check_interval: raw.process.check_interval,
@@ -148,22 +353,102 @@ impl Settings {
                 .split(',')
                 .map(|s| s.trim().to_string())
                 .collect(),
+            windows_process_source: raw.process.windows_source,
+            profiles: Self::resolve_profiles(&raw),
             recording_dir: Self::translate_path(raw.paths.recording_dir),
             db_path: Self::translate_path(raw.paths.db_path),
             audio_device: raw.audio.device_name,
+            audio_sources: raw.audio.sources,
+            audio_backend: raw.audio.backend,
+            fixture_path: raw.audio.fixture_path.map(Self::translate_path),
+            synthetic_pattern: raw.audio.synthetic_pattern,
+            audio_format: raw.audio.format,
             silence_threshold: raw.audio.silence_threshold,
+            vad_hangover_ms: raw.audio.vad_hangover_ms,
+            vad_pre_roll_ms: raw.audio.vad_pre_roll_ms,
+            vad_mode: raw.audio.vad_mode,
+            max_duration_minutes: raw.audio.max_duration_minutes,
+            pre_roll_secs: raw.audio.pre_roll_secs,
             start_debounce_secs: raw.trigger.start_debounce_secs,
             stop_grace_secs: raw.trigger.stop_grace_secs,
             min_recording_secs: raw.trigger.min_recording_secs,
+            control_addr: raw.control.addr,
+            task_retry: RetryPolicy {
+                max_attempts: raw.tasks.max_attempts,
+                base_delay_secs: raw.tasks.retry_base_secs,
+                max_delay_secs: raw.tasks.retry_max_secs,
+            },
+            task_concurrency: TaskConcurrency {
+                per_type: raw
+                    .tasks
+                    .concurrency
+                    .into_iter()
+                    .map(|(task_type, limit)| (task_type, limit.max(1)))
+                    .collect(),
+                llm_calls: raw.tasks.llm_concurrency.max(1),
+            },
         })
     }
     pub fn get_audio_recording_settings() -> Result<AudioRecordingSettings, anyhow::Error> {
         let s = Config::builder()
             .set_default("paths.recording_dir", crate::domain::constants::APP_DIRS[0])?
+            .set_default(
+                "process.windows_source",
+                crate::domain::constants::DEFAULT_WINDOWS_PROCESS_SOURCE,
+            )?
             .set_default(
                 "audio.silence_threshold",
                 crate::domain::constants::DEFAULT_SILENCE_THRESHOLD,
             )?
+            .set_default(
+                "audio.vad_hangover_ms",
+                crate::domain::constants::DEFAULT_VAD_HANGOVER_MS,
+            )?
+            .set_default(
+                "audio.vad_pre_roll_ms",
+                crate::domain::constants::DEFAULT_VAD_PRE_ROLL_MS,
+            )?
+            .set_default("audio.vad_mode", crate::domain::constants::DEFAULT_VAD_MODE)?
+            .set_default(
+                "audio.format",
+                crate::domain::constants::DEFAULT_AUDIO_FORMAT,
+            )?
+            .set_default(
+                "audio.max_duration_minutes",
+                crate::domain::constants::DEFAULT_MAX_DURATION_MINUTES,
+            )?
+            .set_default(
+                "audio.pre_roll_secs",
+                crate::domain::constants::DEFAULT_PRE_ROLL_SECS,
+            )?
+            .set_default(
+                "audio.backend",
+                crate::domain::constants::DEFAULT_CAPTURE_BACKEND,
+            )?
+            .set_default(
+                "audio.synthetic_pattern",
+                crate::domain::constants::DEFAULT_SYNTHETIC_PATTERN,
+            )?
+            .set_default(
+                "control.addr",
+                crate::domain::constants::DEFAULT_CONTROL_ADDR,
+            )?
+            .set_default(
+                "tasks.max_attempts",
+                crate::domain::constants::DEFAULT_TASK_MAX_ATTEMPTS,
+            )?
+            .set_default(
+                "tasks.retry_base_secs",
+                crate::domain::constants::DEFAULT_TASK_RETRY_BASE_SECS,
+            )?
+            .set_default(
+                "tasks.retry_max_secs",
+                crate::domain::constants::DEFAULT_TASK_RETRY_MAX_SECS,
+            )?
+            .set_default(
+                "tasks.llm_concurrency",
+                crate::domain::constants::DEFAULT_LLM_CONCURRENCY as u64,
+            )?
             .add_source(File::with_name(crate::domain::constants::CONFIG_PATH).required(false))
             .add_source(Environment::default().separator("__"))
             .build()?;
@@ -171,13 +456,250 @@ impl Settings {
         Ok(AudioRecordingSettings {
             recording_dir: Self::translate_path(raw.paths.recording_dir),
             audio_device: raw.audio.device_name,
+            audio_sources: raw.audio.sources,
+            audio_backend: raw.audio.backend,
+            fixture_path: raw.audio.fixture_path.map(Self::translate_path),
+            synthetic_pattern: raw.audio.synthetic_pattern,
+            audio_format: raw.audio.format,
             silence_threshold: raw.audio.silence_threshold,
+            vad_hangover_ms: raw.audio.vad_hangover_ms,
+            vad_pre_roll_ms: raw.audio.vad_pre_roll_ms,
+            vad_mode: raw.audio.vad_mode,
+            max_duration_minutes: raw.audio.max_duration_minutes,
+            pre_roll_secs: raw.audio.pre_roll_secs,
             sample_rate: crate::domain::constants::TARGET_SAMPLE_RATE,
             channels: crate::domain::constants::TARGET_CHANNELS,
         })
     }
-    pub fn default_tasks_path() -> PathBuf {
-        PathBuf::from(crate::domain::constants::TASKS_PATH)
+    pub fn recording_config(&self) -> RecordingConfig {
+        RecordingConfig {
+            sample_rate: crate::domain::constants::DEFAULT_SAMPLE_RATE,
+            channels: crate::domain::constants::DEFAULT_CHANNELS,
+            format: self.audio_format,
+            sources: Self::resolve_sources(&self.audio_device, &self.audio_sources),
+            backend: self.audio_backend,
+            fixture_path: self.fixture_path.clone(),
+            synthetic_pattern: self.synthetic_pattern.clone(),
+            silence_threshold: self.silence_threshold,
+            vad_hangover_ms: self.vad_hangover_ms,
+            vad_pre_roll_ms: self.vad_pre_roll_ms,
+            vad_mode: self.vad_mode,
+            max_segment_secs: self.max_duration_minutes * 60,
+            pre_roll_secs: self.pre_roll_secs,
+            session_id: None,
+        }
+    }
+    fn resolve_llm_cache(raw: &RawSettings) -> LlmCacheConfig {
+        let cache = &raw.llm.cache;
+        LlmCacheConfig {
+            enabled: cache.enabled.unwrap_or(true),
+            dir: Self::translate_path(
+                cache
+                    .dir
+                    .clone()
+                    .unwrap_or_else(|| crate::domain::constants::DEFAULT_LLM_CACHE_DIR.to_string()),
+            ),
+            ttl_secs: cache
+                .ttl_days
+                .unwrap_or(crate::domain::constants::DEFAULT_LLM_CACHE_TTL_DAYS)
+                * 86_400,
+            max_bytes: cache
+                .max_size_mb
+                .unwrap_or(crate::domain::constants::DEFAULT_LLM_CACHE_MAX_SIZE_MB)
+                * 1024
+                * 1024,
+        }
+    }
+    fn resolve_llm(raw: &RawSettings, require_keys: bool) -> Result<LlmRouting, anyhow::Error> {
+        let legacy_model = env::var("GEMINI_MODEL").unwrap_or_else(|_| {
+            raw.gemini
+                .as_ref()
+                .map(|g| g.model.clone())
+                .unwrap_or_else(|| crate::domain::constants::DEFAULT_GEMINI_MODEL.to_string())
+        });
+        let legacy_ollama =
+            legacy_model.strip_prefix(crate::domain::constants::LEGACY_OLLAMA_MODEL_PREFIX);
+        let builtin = |kind: LlmProviderKind, model: &str| LlmProviderSettings {
+            kind,
+            model: model.to_string(),
+            base_url: None,
+            api_key_env: None,
+            timeout_secs: None,
+            requests_per_minute: None,
+            burst: None,
+            max_retries: None,
+            inline_max_mb: None,
+            upload_base_url: None,
+        };
+        let mut providers = HashMap::from([
+            (
+                crate::domain::constants::LLM_PROVIDER_GEMINI.to_string(),
+                builtin(
+                    LlmProviderKind::Gemini,
+                    match legacy_ollama {
+                        Some(_) => crate::domain::constants::DEFAULT_GEMINI_MODEL,
+                        None => &legacy_model,
+                    },
+                ),
+            ),
+            (
+                crate::domain::constants::LLM_PROVIDER_OLLAMA.to_string(),
+                builtin(LlmProviderKind::Ollama, legacy_ollama.unwrap_or_default()),
+            ),
+            (
+                crate::domain::constants::LLM_PROVIDER_WHISPER.to_string(),
+                builtin(LlmProviderKind::Whisper, ""),
+            ),
+        ]);
+        providers.extend(raw.llm.providers.clone());
+        let (text_default, transcription_default) = match legacy_ollama {
+            Some(_) => (
+                crate::domain::constants::LLM_PROVIDER_OLLAMA,
+                crate::domain::constants::LLM_PROVIDER_WHISPER,
+            ),
+            None => (
+                crate::domain::constants::LLM_PROVIDER_GEMINI,
+                crate::domain::constants::LLM_PROVIDER_GEMINI,
+            ),
+        };
+        let resolve_one = |role: &str, name: &str, transcribes: bool| {
+            let provider = providers.get(name).ok_or_else(|| {
+                anyhow::anyhow!("llm.routes.{} refers to unknown provider {}", role, name)
+            })?;
+            let supported = if transcribes {
+                provider.kind.can_transcribe()
+            } else {
+                provider.kind.can_generate()
+            };
+            if !supported {
+                anyhow::bail!(
+                    "llm.routes.{}: provider {} ({:?}) does not support {}",
+                    role,
+                    name,
+                    provider.kind,
+                    role
+                );
+            }
+            if provider.model.is_empty() && provider.kind != LlmProviderKind::Whisper {
+                anyhow::bail!("llm.providers.{}.model must be set", name);
+            }
+            let base_url = provider
+                .base_url
+                .clone()
+                .unwrap_or_else(|| provider.kind.default_base_url().to_string());
+            if base_url.is_empty() && provider.kind == LlmProviderKind::OpenAi {
+                anyhow::bail!("llm.providers.{}.base_url must be set", name);
+            }
+            let base_url = base_url.trim_end_matches('/').to_string();
+            let upload_base_url = match (&provider.upload_base_url, provider.kind) {
+                (Some(url), _) => url.trim_end_matches('/').to_string(),
+                (None, LlmProviderKind::Gemini) => match base_url.rsplit_once('/') {
+                    Some((origin, version)) => format!("{}/upload/{}", origin, version),
+                    None => base_url.clone(),
+                },
+                (None, _) => String::new(),
+            };
+            let api_key_env = provider.api_key_env.clone().or_else(|| {
+                (provider.kind == LlmProviderKind::Gemini)
+                    .then(|| crate::domain::constants::DEFAULT_GEMINI_API_KEY_ENV.to_string())
+            });
+            let api_key = match api_key_env {
+                Some(var) => match env::var(&var) {
+                    Ok(key) => Some(key),
+                    Err(_) if require_keys => anyhow::bail!(
+                        "{} must be set in environment or .env (llm provider {})",
+                        var,
+                        name
+                    ),
+                    Err(_) => None,
+                },
+                None => None,
+            };
+            Ok(LlmProvider {
+                name: name.to_string(),
+                kind: provider.kind,
+                model: provider.model.clone(),
+                base_url,
+                api_key,
+                timeout_secs: provider
+                    .timeout_secs
+                    .unwrap_or(crate::domain::constants::DEFAULT_LLM_TIMEOUT_SECS),
+                requests_per_minute: provider.requests_per_minute.filter(|rpm| *rpm > 0),
+                burst: provider
+                    .burst
+                    .unwrap_or(crate::domain::constants::DEFAULT_LLM_BURST)
+                    .max(1),
+                max_retries: provider
+                    .max_retries
+                    .unwrap_or(crate::domain::constants::DEFAULT_LLM_MAX_RETRIES),
+                inline_max_bytes: provider
+                    .inline_max_mb
+                    .unwrap_or(crate::domain::constants::DEFAULT_GEMINI_INLINE_MAX_MB)
+                    * 1024
+                    * 1024,
+                upload_base_url,
+            })
+        };
+        let resolve = |role: &str, route: &Option<LlmRoute>, transcribes: bool| {
+            let names = match route {
+                Some(route) => route.names(),
+                None if transcribes => vec![transcription_default.to_string()],
+                None => vec![text_default.to_string()],
+            };
+            if names.iter().all(|n| n.is_empty()) {
+                anyhow::bail!("llm.routes.{} must name at least one provider", role);
+            }
+            names
+                .iter()
+                .filter(|n| !n.is_empty())
+                .map(|name| resolve_one(role, name, transcribes))
+                .collect::<Result<Vec<LlmProvider>, anyhow::Error>>()
+        };
+        let routes = &raw.llm.routes;
+        Ok(LlmRouting {
+            transcription: resolve("transcription", &routes.transcription, true)?,
+            summarization: resolve("summarization", &routes.summarization, false)?,
+            novelization: resolve("novelization", &routes.novelization, false)?,
+            evaluation: resolve("evaluation", &routes.evaluation, false)?,
+        })
+    }
+    fn resolve_profiles(raw: &RawSettings) -> Vec<RecordingProfile> {
+        let mut profiles = raw.profiles.clone();
+        let processes: Vec<String> = raw
+            .process
+            .names
+            .split(',')
+            .map(|s| s.trim().to_string())
+            .filter(|s| !s.is_empty())
+            .collect();
+        if !processes.is_empty() {
+            profiles.push(RecordingProfile {
+                name: crate::domain::constants::DEFAULT_PROFILE_NAME.to_string(),
+                processes,
+                rule: None,
+                sources: Vec::new(),
+                silence_threshold: None,
+                start_debounce_secs: None,
+                stop_grace_secs: None,
+                min_recording_secs: None,
+                summary_prompt: None,
+                tags: Vec::new(),
+            });
+        }
+        profiles
+    }
+    pub fn resolve_sources(
+        device_name: &Option<String>,
+        sources: &[AudioSource],
+    ) -> Vec<AudioSource> {
+        if !sources.is_empty() {
+            return sources.to_vec();
+        }
+        vec![AudioSource {
+            name: crate::domain::constants::DEFAULT_AUDIO_SOURCE_NAME.to_string(),
+            device_name: device_name.clone(),
+            application: None,
+        }]
     }
     fn translate_path(path: String) -> PathBuf {
         if cfg!(windows) {
@@ -1,54 +1,433 @@
-use crate::domain::Task;
+use crate::domain::constants::{
+    STATUS_CANCELLED, STATUS_COMPLETED, STATUS_FAILED, STATUS_PENDING, STATUS_PROCESSING,
+};
 use crate::domain::TaskRepository as TaskRepositoryTrait;
-use chrono::Utc;
-use std::fs;
-use std::path::PathBuf;
+use crate::domain::{Task, TaskProfile};
+use chrono::{DateTime, Utc};
+use sqlx::sqlite::SqliteRow;
+use sqlx::{Row, SqliteConnection, SqlitePool};
+use std::path::Path;
+use std::sync::Arc;
+use tokio::sync::{Mutex, Notify};
+use tracing::{error, info, warn};
 use uuid::Uuid;
 pub struct TaskRepository {
-    path: PathBuf,
+    pool: SqlitePool,
+    notify: Arc<Notify>,
+    data_version: Mutex<Option<(SqliteConnection, Option<i64>)>>,
 }
 impl TaskRepository {
-    pub fn new(path: impl Into<PathBuf>) -> Self {
-        Self { path: path.into() }
+    pub fn new(pool: SqlitePool) -> Self {
+        Self {
+            pool,
+            notify: Arc::new(Notify::new()),
+            data_version: Mutex::new(None),
+        }
+    }
+    async fn wait_for_external_change(&self) {
+        let mut watch = self.data_version.lock().await;
+        loop {
+            if watch.is_none() {
+                match self.pool.acquire().await {
+                    Ok(conn) => *watch = Some((conn.detach(), None)),
+                    Err(e) => {
+                        warn!("Cannot watch the task queue for other processes: {}", e);
+                        return std::future::pending().await;
+                    }
+                }
+            }
+            if let Some((conn, last)) = watch.as_mut() {
+                match sqlx::query_scalar::<_, i64>(crate::domain::constants::SQL_DATA_VERSION)
+                    .fetch_one(&mut *conn)
+                    .await
+                {
+                    Ok(version) if last.is_some_and(|last| last != version) => {
+                        *last = Some(version);
+                        return;
+                    }
+                    Ok(version) => *last = Some(version),
+                    Err(e) => {
+                        warn!("Failed to read the task queue data version: {}", e);
+                        *watch = None;
+                    }
+                }
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(
+                crate::domain::constants::TASK_WAKE_POLL_MS,
+            ))
+            .await;
+        }
     }
-    pub fn ensure_file(&self) {
-        if !self.path.exists() {
-            fs::write(&self.path, "[]").unwrap();
+    async fn insert(&self, task: &Task) -> bool {
+        match sqlx::query(crate::domain::constants::SQL_INSERT_TASK)
+            .bind(&task.id)
+            .bind(task.created_at)
+            .bind(&task.status)
+            .bind(&task.task_type)
+            .bind(serde_json::to_string(&task.file_paths).unwrap_or_else(|_| "[]".to_string()))
+            .bind(&task.session_id)
+            .bind(
+                task.profile
+                    .as_ref()
+                    .and_then(|p| serde_json::to_string(p).ok()),
+            )
+            .bind(&task.lease_owner)
+            .bind(task.lease_expires_at)
+            .bind(task.attempts)
+            .bind(&task.last_error)
+            .bind(task.next_attempt_at)
+            .bind(serde_json::to_string(&task.depends_on).unwrap_or_else(|_| "[]".to_string()))
+            .bind(&task.date)
+            .execute(&self.pool)
+            .await
+        {
+            Ok(result) => result.rows_affected() > 0,
+            Err(e) => {
+                error!("Failed to insert task {}: {}", task.id, e);
+                false
+            }
         }
     }
-    fn save(&self, tasks: &[Task]) {
-        let content = serde_json::to_string_pretty(tasks).unwrap();
-        let tmp_path = self.path.with_extension("tmp");
-        fs::write(&tmp_path, content).unwrap();
-        fs::rename(&tmp_path, &self.path).unwrap();
+    pub async fn import_json(&self, path: &Path) -> anyhow::Result<usize> {
+        let content = std::fs::read_to_string(path)?;
+        let tasks: Vec<Task> = serde_json::from_str(&content)?;
+        let mut imported = 0;
+        for task in &tasks {
+            if self.insert(task).await {
+                imported += 1;
+            }
+        }
+        let archive = path.with_extension(format!(
+            "json.{}",
+            crate::domain::constants::TASKS_IMPORTED_SUFFIX
+        ));
+        std::fs::rename(path, &archive)?;
+        info!(
+            "Imported {} of {} tasks from {:?} (archived to {:?})",
+            imported,
+            tasks.len(),
+            path,
+            archive
+        );
+        Ok(imported)
     }
+    pub async fn import_legacy(&self) {
+        let path = Path::new(crate::domain::constants::TASKS_PATH);
+        if !path.exists() {
+            return;
+        }
+        if let Err(e) = self.import_json(path).await {
+            warn!("Failed to import legacy task queue {:?}: {}", path, e);
+        }
+    }
+}
+fn lease_deadline(lease_secs: u64) -> DateTime<Utc> {
+    Utc::now() + chrono::Duration::seconds(lease_secs as i64)
+}
+fn failure_status(retry_at: Option<DateTime<Utc>>) -> &'static str {
+    if retry_at.is_some() {
+        STATUS_PENDING
+    } else {
+        STATUS_FAILED
+    }
+}
+fn decode_task(row: &SqliteRow) -> Result<Task, sqlx::Error> {
+    let file_paths: String = row.try_get("file_paths")?;
+    let profile: Option<String> = row.try_get("profile")?;
+    let depends_on: String = row.try_get("depends_on")?;
+    let decode = |column: &str, e: serde_json::Error| sqlx::Error::ColumnDecode {
+        index: column.to_string(),
+        source: Box::new(e),
+    };
+    Ok(Task {
+        id: row.try_get("id")?,
+        created_at: row.try_get("created_at")?,
+        status: row.try_get("status")?,
+        task_type: row.try_get("task_type")?,
+        file_paths: serde_json::from_str(&file_paths).map_err(|e| decode("file_paths", e))?,
+        session_id: row.try_get("session_id")?,
+        profile: profile
+            .map(|p| serde_json::from_str(&p))
+            .transpose()
+            .map_err(|e| decode("profile", e))?,
+        lease_owner: row.try_get("lease_owner")?,
+        lease_expires_at: row.try_get("lease_expires_at")?,
+        attempts: row.try_get("attempts")?,
+        last_error: row.try_get("last_error")?,
+        next_attempt_at: row.try_get("next_attempt_at")?,
+        depends_on: serde_json::from_str(&depends_on).map_err(|e| decode("depends_on", e))?,
+        date: row.try_get("date")?,
+    })
 }
+fn row_to_task(row: &SqliteRow) -> Option<Task> {
+    match decode_task(row) {
+        Ok(task) => Some(task),
+        Err(e) => {
+            let id: Option<String> = row.try_get("id").ok();
+            error!("Skipping undecodable task row {:?}: {}", id, e);
+            None
+        }
+    }
+}
+#[async_trait::async_trait]
 impl TaskRepositoryTrait for TaskRepository {
-    fn add(&self, task_type: &str, file_paths: Vec<String>) -> Task {
-        let mut tasks = self.load();
+    async fn add(&self, task_type: &str, file_paths: Vec<String>) -> Task {
+        self.add_with_session(task_type, file_paths, None).await
+    }
+    async fn add_with_session(
+        &self,
+        task_type: &str,
+        file_paths: Vec<String>,
+        session_id: Option<String>,
+    ) -> Task {
+        self.add_with_profile(task_type, file_paths, session_id, None)
+            .await
+    }
+    async fn add_with_profile(
+        &self,
+        task_type: &str,
+        file_paths: Vec<String>,
+        session_id: Option<String>,
+        profile: Option<TaskProfile>,
+    ) -> Task {
         let task = Task {
             id: Uuid::now_v7().to_string(),
             created_at: Utc::now(),
-            status: "pending".to_string(),
+            status: STATUS_PENDING.to_string(),
             task_type: task_type.to_string(),
             file_paths,
+            session_id,
+            profile,
+            lease_owner: None,
+            lease_expires_at: None,
+            attempts: 0,
+            last_error: None,
+            next_attempt_at: None,
+            depends_on: Vec::new(),
+            date: None,
         };
-        tasks.push(task.clone());
-        self.save(&tasks);
+        self.add_task(task.clone()).await;
         task
     }
-    fn load(&self) -> Vec<Task> {
-        self.ensure_file();
-        let content = fs::read_to_string(&self.path).unwrap();
-        serde_json::from_str(&content).unwrap()
+    async fn add_task(&self, task: Task) -> bool {
+        let added = self.insert(&task).await;
+        if added {
+            self.notify.notify_one();
+        }
+        added
+    }
+    async fn load(&self) -> Vec<Task> {
+        match sqlx::query(crate::domain::constants::SQL_QUERY_TASKS)
+            .fetch_all(&self.pool)
+            .await
+        {
+            Ok(rows) => rows.iter().filter_map(row_to_task).collect(),
+            Err(e) => {
+                error!("Failed to load tasks: {}", e);
+                Vec::new()
+            }
+        }
+    }
+    async fn get(&self, id: &str) -> Option<Task> {
+        match sqlx::query(crate::domain::constants::SQL_GET_TASK)
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await
+        {
+            Ok(row) => row.as_ref().and_then(row_to_task),
+            Err(e) => {
+                error!("Failed to get task {}: {}", id, e);
+                None
+            }
+        }
     }
-    fn update_status(&self, id: &str, status: &str) {
-        let mut tasks = self.load();
-        if let Some(task) = tasks.iter_mut().find(|t| t.id == id) {
-            task.status = status.to_string();
-            self.save(&tasks);
-        } else {
-            panic!("Task with id {} not found", id);
+    async fn update_status(&self, id: &str, status: &str, worker_id: Option<&str>) -> bool {
+        match sqlx::query(crate::domain::constants::SQL_UPDATE_TASK_STATUS)
+            .bind(status)
+            .bind(id)
+            .bind(worker_id)
+            .execute(&self.pool)
+            .await
+        {
+            Ok(result) => result.rows_affected() > 0,
+            Err(e) => {
+                error!("Failed to set task {} to {}: {}", id, status, e);
+                false
+            }
         }
     }
+    async fn claim(
+        &self,
+        worker_id: &str,
+        lease_secs: u64,
+        exclude_types: &[String],
+    ) -> Option<Task> {
+        match sqlx::query(crate::domain::constants::SQL_CLAIM_TASK)
+            .bind(STATUS_PROCESSING)
+            .bind(worker_id)
+            .bind(lease_deadline(lease_secs))
+            .bind(STATUS_PENDING)
+            .bind(Utc::now())
+            .bind(serde_json::to_string(exclude_types).unwrap_or_else(|_| "[]".to_string()))
+            .bind(STATUS_COMPLETED)
+            .fetch_optional(&self.pool)
+            .await
+        {
+            Ok(row) => row.as_ref().and_then(row_to_task),
+            Err(e) => {
+                error!("Failed to claim a task for {}: {}", worker_id, e);
+                None
+            }
+        }
+    }
+    async fn heartbeat(&self, id: &str, worker_id: &str, lease_secs: u64) -> bool {
+        match sqlx::query(crate::domain::constants::SQL_HEARTBEAT_TASK)
+            .bind(lease_deadline(lease_secs))
+            .bind(id)
+            .bind(worker_id)
+            .bind(STATUS_PROCESSING)
+            .execute(&self.pool)
+            .await
+        {
+            Ok(result) => result.rows_affected() > 0,
+            Err(e) => {
+                error!("Failed to extend the lease of task {}: {}", id, e);
+                false
+            }
+        }
+    }
+    async fn expired(&self) -> Vec<Task> {
+        match sqlx::query(crate::domain::constants::SQL_EXPIRED_TASKS)
+            .bind(STATUS_PROCESSING)
+            .bind(Utc::now())
+            .fetch_all(&self.pool)
+            .await
+        {
+            Ok(rows) => rows.iter().filter_map(row_to_task).collect(),
+            Err(e) => {
+                error!("Failed to look up expired task leases: {}", e);
+                Vec::new()
+            }
+        }
+    }
+    async fn reclaim(
+        &self,
+        id: &str,
+        error: &str,
+        retry_at: Option<DateTime<Utc>>,
+    ) -> Option<Task> {
+        match sqlx::query(crate::domain::constants::SQL_RECLAIM_TASK)
+            .bind(failure_status(retry_at))
+            .bind(error)
+            .bind(retry_at)
+            .bind(id)
+            .bind(STATUS_PROCESSING)
+            .bind(Utc::now())
+            .fetch_optional(&self.pool)
+            .await
+        {
+            Ok(row) => row.as_ref().and_then(row_to_task),
+            Err(e) => {
+                error!("Failed to reclaim task {}: {}", id, e);
+                None
+            }
+        }
+    }
+    async fn record_failure(
+        &self,
+        id: &str,
+        worker_id: &str,
+        error: &str,
+        retry_at: Option<DateTime<Utc>>,
+    ) -> bool {
+        match sqlx::query(crate::domain::constants::SQL_FAIL_TASK)
+            .bind(failure_status(retry_at))
+            .bind(error)
+            .bind(retry_at)
+            .bind(id)
+            .bind(STATUS_PROCESSING)
+            .bind(worker_id)
+            .execute(&self.pool)
+            .await
+        {
+            Ok(result) => result.rows_affected() > 0,
+            Err(e) => {
+                error!("Failed to record failure of task {}: {}", id, e);
+                false
+            }
+        }
+    }
+    async fn requeue(&self, id: &str) -> bool {
+        let requeued = match sqlx::query(crate::domain::constants::SQL_REQUEUE_TASK)
+            .bind(STATUS_PENDING)
+            .bind(id)
+            .execute(&self.pool)
+            .await
+        {
+            Ok(result) => result.rows_affected() > 0,
+            Err(e) => {
+                error!("Failed to requeue task {}: {}", id, e);
+                false
+            }
+        };
+        if requeued {
+            self.notify.notify_one();
+        }
+        requeued
+    }
+    async fn update_dependencies(&self, id: &str, depends_on: &[String]) -> bool {
+        sqlx::query(crate::domain::constants::SQL_UPDATE_TASK_DEPENDENCIES)
+            .bind(serde_json::to_string(depends_on).unwrap_or_else(|_| "[]".to_string()))
+            .bind(id)
+            .bind(STATUS_PENDING)
+            .execute(&self.pool)
+            .await
+            .is_ok_and(|result| result.rows_affected() > 0)
+    }
+    async fn cascade(&self, id: &str, from: &str, to: &str, reason: Option<&str>) -> Vec<Task> {
+        let rows = match sqlx::query(crate::domain::constants::SQL_CASCADE_TASKS)
+            .bind(id)
+            .bind(to)
+            .bind(reason)
+            .bind(id)
+            .bind(from)
+            .fetch_all(&self.pool)
+            .await
+        {
+            Ok(rows) => rows,
+            Err(e) => {
+                error!("Failed to move dependents of {} to {}: {}", id, to, e);
+                return Vec::new();
+            }
+        };
+        if to == STATUS_PENDING && !rows.is_empty() {
+            self.notify.notify_one();
+        }
+        rows.iter().filter_map(row_to_task).collect()
+    }
+    async fn prune(&self, before: DateTime<Utc>) -> u64 {
+        match sqlx::query(crate::domain::constants::SQL_PRUNE_TASKS)
+            .bind(STATUS_COMPLETED)
+            .bind(STATUS_FAILED)
+            .bind(STATUS_CANCELLED)
+            .bind(before)
+            .execute(&self.pool)
+            .await
+        {
+            Ok(result) => result.rows_affected(),
+            Err(e) => {
+                error!("Failed to prune finished tasks: {}", e);
+                0
+            }
+        }
+    }
+    async fn wait_for_task(&self, timeout: std::time::Duration) {
+        let _ = tokio::time::timeout(timeout, async {
+            tokio::select! {
+                _ = self.notify.notified() => {}
+                _ = self.wait_for_external_change() => {}
+            }
+        })
+        .await;
+    }
 }
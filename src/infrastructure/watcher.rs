@@ -1,10 +1,11 @@
 use crate::domain::TaskRepository as TaskRepositoryTrait;
-use crate::infrastructure::tasks::TaskRepository;
 use notify::{Config, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
 use std::path::PathBuf;
+use std::sync::Arc;
 use tracing::{error, info};
 pub struct FileWatcher {
     path: PathBuf,
+    task_repository: Arc<dyn TaskRepositoryTrait>,
 }
 impl crate::domain::FileWatcher for FileWatcher {
     fn start(&self) {
@@ -12,11 +13,16 @@ impl crate::domain::FileWatcher for FileWatcher {
     }
 }
 impl FileWatcher {
-    pub fn new(path: impl Into<PathBuf>) -> Self {
-        Self { path: path.into() }
+    pub fn new(path: impl Into<PathBuf>, task_repository: Arc<dyn TaskRepositoryTrait>) -> Self {
+        Self {
+            path: path.into(),
+            task_repository,
+        }
     }
     pub fn start(&self) {
         let path = self.path.clone();
+        let repo = self.task_repository.clone();
+        let runtime = tokio::runtime::Handle::current();
         let (tx, rx) = std::sync::mpsc::channel();
         let tx = tx.clone();
         let config = Config::default().with_poll_interval(std::time::Duration::from_secs(
@@ -46,23 +52,17 @@ impl FileWatcher {
                     match ext.to_lowercase().as_str() {
                         "wav" | "flac" => {
                             info!("New audio file: {:?}", path);
-                            let repo = TaskRepository::new(
-                                crate::infrastructure::settings::Settings::default_tasks_path(),
-                            );
-                            repo.add(
+                            runtime.block_on(repo.add(
                                 crate::domain::constants::TASK_TYPE_PROCESS_SESSION,
                                 vec![path.to_string_lossy().to_string()],
-                            );
+                            ));
                         }
                         "jsonl" => {
                             info!("New activity log: {:?}", path);
-                            let repo = TaskRepository::new(
-                                crate::infrastructure::settings::Settings::default_tasks_path(),
-                            );
-                            repo.add(
+                            runtime.block_on(repo.add(
                                 crate::domain::constants::TASK_TYPE_SYNC_ACTIVITY,
                                 vec![path.to_string_lossy().to_string()],
-                            );
+                            ));
                         }
                         _ => {}
                     }
@@ -0,0 +1,223 @@
+use base64::Engine;
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader};
+use std::process::{Child, Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use tracing::{info, warn};
+const FEED_SCRIPT: &str = r#"
+$ErrorActionPreference = 'Stop'
+[Console]::OutputEncoding = [System.Text.Encoding]::UTF8
+Add-Type -Namespace VLog -Name Win32 -MemberDefinition @'
+[DllImport("user32.dll")] public static extern System.IntPtr GetForegroundWindow();
+[DllImport("user32.dll")] public static extern uint GetWindowThreadProcessId(System.IntPtr hWnd, out uint pid);
+[DllImport("user32.dll", CharSet = CharSet.Unicode)] public static extern int GetWindowText(System.IntPtr hWnd, System.Text.StringBuilder text, int count);
+'@
+$out = [Console]::Out
+$withCmdline = __CMDLINE__
+$known = @{}
+$foreground = ''
+try {
+    while ($true) {
+        $seen = @{}
+        $added = @()
+        foreach ($p in Get-Process -ErrorAction SilentlyContinue) {
+            $seen[$p.Id] = $true
+            $title = $p.MainWindowTitle -replace "`t", ' '
+            if (-not $known.ContainsKey($p.Id)) {
+                $added += $p
+                $known[$p.Id] = $title
+            } elseif ($known[$p.Id] -ne $title) {
+                $out.WriteLine("~`t$($p.Id)`t$title")
+                $known[$p.Id] = $title
+            }
+        }
+        if ($added.Count -gt 0) {
+            $cmdlines = @{}
+            if ($withCmdline) {
+                foreach ($c in Get-CimInstance Win32_Process -Property ProcessId,CommandLine -ErrorAction SilentlyContinue) {
+                    $cmdlines[[int]$c.ProcessId] = $c.CommandLine -replace "`t", ' '
+                }
+            }
+            foreach ($p in $added) {
+                $out.WriteLine("+`t$($p.Id)`t$($p.ProcessName)`t$($p.Path)`t$($cmdlines[$p.Id])`t$($known[$p.Id])")
+            }
+        }
+        foreach ($id in @($known.Keys)) {
+            if (-not $seen.ContainsKey($id)) {
+                $out.WriteLine("-`t$id")
+                $known.Remove($id)
+            }
+        }
+        $hwnd = [VLog.Win32]::GetForegroundWindow()
+        $fgpid = [uint32]0
+        [void][VLog.Win32]::GetWindowThreadProcessId($hwnd, [ref]$fgpid)
+        $text = New-Object System.Text.StringBuilder 512
+        [void][VLog.Win32]::GetWindowText($hwnd, $text, 512)
+        $current = "$fgpid`t" + ($text.ToString() -replace "`t", ' ')
+        if ($current -ne $foreground) {
+            $out.WriteLine("fg`t$current")
+            $foreground = $current
+        }
+        $out.WriteLine('.')
+        $out.Flush()
+        Start-Sleep -Milliseconds __INTERVAL__
+    }
+} catch {
+    exit 1
+}
+"#;
+#[derive(Debug, Clone)]
+pub struct FeedProcess {
+    pub name: String,
+    pub path: String,
+    pub cmdline: String,
+    pub titles: Vec<String>,
+}
+#[derive(Default)]
+struct FeedState {
+    processes: HashMap<u32, (String, String, String, String)>,
+    foreground: Option<(u32, String)>,
+    ready: bool,
+}
+impl FeedState {
+    fn apply(&mut self, line: &str) {
+        let fields: Vec<&str> = line.trim_end_matches('\r').splitn(6, '\t').collect();
+        match fields.as_slice() {
+            ["+", pid, name, path, cmdline, title] => {
+                if let Ok(pid) = pid.parse() {
+                    self.processes.insert(
+                        pid,
+                        (
+                            name.to_string(),
+                            path.to_string(),
+                            cmdline.to_string(),
+                            title.to_string(),
+                        ),
+                    );
+                }
+            }
+            ["~", pid, title] => {
+                if let Some(entry) = pid.parse().ok().and_then(|p| self.processes.get_mut(&p)) {
+                    entry.3 = title.to_string();
+                }
+            }
+            ["-", pid] => {
+                if let Ok(pid) = pid.parse::<u32>() {
+                    self.processes.remove(&pid);
+                }
+            }
+            ["fg", pid, title] => {
+                self.foreground = pid.parse().ok().map(|pid| (pid, title.to_string()));
+            }
+            ["."] => self.ready = true,
+            _ => {}
+        }
+    }
+}
+pub struct WindowsProcessFeed {
+    child: Child,
+    state: Arc<Mutex<FeedState>>,
+    alive: Arc<AtomicBool>,
+    reader: Option<JoinHandle<()>>,
+}
+impl WindowsProcessFeed {
+    pub fn spawn(interval_ms: u64, with_cmdline: bool) -> anyhow::Result<Self> {
+        let script = FEED_SCRIPT
+            .replace("__INTERVAL__", &interval_ms.to_string())
+            .replace("__CMDLINE__", if with_cmdline { "$true" } else { "$false" });
+        let utf16: Vec<u8> = script
+            .encode_utf16()
+            .flat_map(|unit| unit.to_le_bytes())
+            .collect();
+        let encoded = base64::engine::general_purpose::STANDARD.encode(utf16);
+        let mut child = Command::new(crate::domain::constants::POWERSHELL_PATH)
+            .args([
+                "-NoLogo",
+                "-NoProfile",
+                "-NonInteractive",
+                "-EncodedCommand",
+                &encoded,
+            ])
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .map_err(|e| anyhow::anyhow!("Failed to start Windows process feed: {}", e))?;
+        let stdout = child
+            .stdout
+            .take()
+            .ok_or_else(|| anyhow::anyhow!("Windows process feed produced no stdout"))?;
+        info!(
+            "Windows process feed started (pid={}, interval={}ms)",
+            child.id(),
+            interval_ms
+        );
+        let state = Arc::new(Mutex::new(FeedState::default()));
+        let alive = Arc::new(AtomicBool::new(true));
+        let reader_state = state.clone();
+        let reader_alive = alive.clone();
+        let reader = std::thread::spawn(move || {
+            for line in BufReader::new(stdout).lines() {
+                match line {
+                    Ok(line) => reader_state.lock().unwrap().apply(&line),
+                    Err(e) => {
+                        warn!("Windows process feed read error: {}", e);
+                        break;
+                    }
+                }
+            }
+            reader_alive.store(false, Ordering::SeqCst);
+        });
+        Ok(Self {
+            child,
+            state,
+            alive,
+            reader: Some(reader),
+        })
+    }
+    pub fn is_alive(&self) -> bool {
+        self.alive.load(Ordering::SeqCst)
+    }
+    pub fn snapshot(&self) -> Option<Vec<FeedProcess>> {
+        let state = self.state.lock().unwrap();
+        if !state.ready {
+            return None;
+        }
+        Some(
+            state
+                .processes
+                .iter()
+                .map(|(pid, (name, path, cmdline, title))| {
+                    let mut titles = Vec::new();
+                    if !title.is_empty() {
+                        titles.push(title.clone());
+                    }
+                    if let Some((_, foreground)) =
+                        state.foreground.as_ref().filter(|(fg, _)| fg == pid)
+                    {
+                        if !foreground.is_empty() && foreground != title {
+                            titles.push(foreground.clone());
+                        }
+                    }
+                    FeedProcess {
+                        name: name.clone(),
+                        path: path.clone(),
+                        cmdline: cmdline.clone(),
+                        titles,
+                    }
+                })
+                .collect(),
+        )
+    }
+}
+impl Drop for WindowsProcessFeed {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+        if let Some(reader) = self.reader.take() {
+            let _ = reader.join();
+        }
+    }
+}
@@ -0,0 +1,4 @@
+pub mod cli;
+pub mod domain;
+pub mod infrastructure;
+pub mod use_cases;
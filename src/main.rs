@@ -1,8 +1,5 @@
-pub mod cli;
-pub mod domain;
-pub mod infrastructure;
-pub mod use_cases;
 use clap::{Parser, Subcommand};
+use vlog_rs::{cli, domain, infrastructure, use_cases};
 #[derive(Parser)]
 #[command(name = "vlog-rs")]
 #[command(about = "Autonomous Life Logger", long_about = None)]
@@ -21,14 +18,20 @@ enum Commands {
     Process {
         #[arg(short, long)]
         file: String,
+        #[command(flatten)]
+        cache: cli::CacheArgs,
     },
     Novel {
         #[arg(short, long)]
         date: String,
+        #[command(flatten)]
+        cache: cli::CacheArgs,
     },
     Evaluate {
         #[arg(short, long)]
         date: String,
+        #[command(flatten)]
+        cache: cli::CacheArgs,
     },
     Sync,
     Pending,
@@ -36,13 +39,23 @@ enum Commands {
     Setup,
     Doctor,
     Devices,
+    Control {
+        #[command(subcommand)]
+        action: cli::control::ControlAction,
+    },
+    Tasks {
+        #[arg(long, global = true)]
+        json: bool,
+        #[command(subcommand)]
+        action: cli::tasks::TasksAction,
+    },
 }
 #[tokio::main]
 async fn main() {
     dotenvy::dotenv().unwrap();
     let file_appender = tracing_appender::rolling::daily(
-        crate::domain::constants::LOGS_DIR,
-        crate::domain::constants::LOG_FILE_NAME,
+        domain::constants::LOGS_DIR,
+        domain::constants::LOG_FILE_NAME,
     );
     let (non_blocking, _guard) = tracing_appender::non_blocking(file_appender);
     tracing_subscriber::fmt()
@@ -71,14 +84,14 @@ async fn main() {
         Some(Commands::Record) => {
             cli::record::run().await.unwrap();
         }
-        Some(Commands::Process { file }) => {
-            cli::process::run(file).await;
+        Some(Commands::Process { file, cache }) => {
+            cli::process::run(file, cache.mode()).await;
         }
-        Some(Commands::Novel { date }) => {
-            cli::novel::run(date).await;
+        Some(Commands::Novel { date, cache }) => {
+            cli::novel::run(date, cache.mode()).await;
         }
-        Some(Commands::Evaluate { date }) => {
-            cli::evaluate::run(date).await;
+        Some(Commands::Evaluate { date, cache }) => {
+            cli::evaluate::run(date, cache.mode()).await;
         }
         Some(Commands::Sync) => {
             cli::sync::run().await;
@@ -98,6 +111,18 @@ async fn main() {
         Some(Commands::Devices) => {
             infrastructure::audio::list_devices();
         }
+        Some(Commands::Control { action }) => {
+            if let Err(e) = cli::control::run(action).await {
+                eprintln!("{}", e);
+                std::process::exit(1);
+            }
+        }
+        Some(Commands::Tasks { action, json }) => {
+            if let Err(e) = cli::tasks::run(action, json).await {
+                eprintln!("{}", e);
+                std::process::exit(1);
+            }
+        }
     }
     drop(_guard);
 }
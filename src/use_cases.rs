@@ -1,4 +1,5 @@
 pub mod build_novel;
+pub mod daily_pipeline;
 pub mod doctor;
 pub mod evaluate;
 pub mod health;
@@ -10,6 +11,7 @@ pub mod sync;
 pub mod sync_activity;
 pub mod synthesis;
 pub mod task_runner;
+pub mod tasks;
 pub mod transcode;
 use crate::domain::Environment;
 use tracing::info;
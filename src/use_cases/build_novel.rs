@@ -1,5 +1,6 @@
 use crate::domain::{Curator, ImageGenerator, Novelizer};
 use crate::infrastructure::fs_utils;
+use anyhow::Context;
 use std::fs;
 use std::path::Path;
 use tracing::info;
@@ -20,15 +21,22 @@ impl BuildNovelUseCase {
             image_generator,
         }
     }
-    pub async fn execute(&self, date: &str) {
+    pub async fn execute(&self, date: &str) -> anyhow::Result<()> {
+        let chapter = self.write_chapter(date).await?;
+        self.generate_image(date, &chapter).await;
+        Ok(())
+    }
+    pub async fn write_chapter(&self, date: &str) -> anyhow::Result<String> {
         let summary_path = crate::domain::constants::SUMMARY_FILE_TEMPLATE.replace("{}", date);
         if !Path::new(&summary_path).exists() {
-            panic!("Summary not found for {}", date);
+            anyhow::bail!("Summary not found for {}", date);
         }
-        let today_summary = fs::read_to_string(&summary_path).unwrap();
+        let today_summary = fs::read_to_string(&summary_path)
+            .with_context(|| format!("Failed to read {}", summary_path))?;
         let novel_path = crate::domain::constants::NOVEL_FILE_TEMPLATE.replace("{}", date);
         let novel_so_far = if Path::new(&novel_path).exists() {
-            fs::read_to_string(&novel_path).unwrap()
+            fs::read_to_string(&novel_path)
+                .with_context(|| format!("Failed to read {}", novel_path))?
         } else {
             String::new()
         };
@@ -36,19 +44,19 @@ impl BuildNovelUseCase {
         let chapter = self
             .novelizer
             .generate_chapter(&today_summary, &novel_so_far)
-            .await;
+            .await?;
         for word in crate::domain::constants::PROHIBITED_WORDS {
             if chapter.to_lowercase().contains(&word.to_lowercase()) {
-                panic!("Prohibited word found: {}", word);
+                anyhow::bail!("Prohibited word found: {}", word);
             }
         }
-        let eval = self.curator.evaluate(&today_summary, &chapter).await;
+        let eval = self.curator.evaluate(&today_summary, &chapter).await?;
         info!(
             "Curator Score: Faithfulness={}, Quality={}, Reason={}",
             eval.faithfulness_score, eval.quality_score, eval.reasoning
         );
         if eval.quality_score < 3 {
-            panic!("Quality verification failed (Score < 3).");
+            anyhow::bail!("Quality verification failed (Score < 3).");
         }
         let content = if novel_so_far.is_empty() {
             chapter.clone()
@@ -57,7 +65,10 @@ impl BuildNovelUseCase {
         };
         fs_utils::atomic_write(&novel_path, content);
         info!("Novel saved to {}", novel_path);
+        Ok(chapter)
+    }
+    pub async fn generate_image(&self, date: &str, text: &str) {
         let photo_path = crate::domain::constants::PHOTO_FILE_TEMPLATE.replace("{}", date);
-        self.image_generator.generate(&chapter, &photo_path).await;
+        self.image_generator.generate(text, &photo_path).await;
     }
 }
@@ -0,0 +1,112 @@
+use crate::domain::constants::{
+    DAILY_PIPELINE_DELAY_SECS, NOVEL_FILE_TEMPLATE, STATUS_PENDING, SUMMARY_FILE_TEMPLATE,
+    TASK_TYPE_BUILD_NOVEL, TASK_TYPE_EVALUATE, TASK_TYPE_GENERATE_IMAGE, TASK_TYPE_SYNC,
+};
+use crate::domain::Task;
+use crate::use_cases::build_novel::BuildNovelUseCase;
+use crate::use_cases::evaluate::EvaluateDailyContentUseCase;
+use crate::use_cases::sync::SyncUseCase;
+use anyhow::Context;
+use chrono::{Duration, Local, NaiveDate, TimeZone, Utc};
+use std::path::Path;
+use tracing::info;
+pub struct DailyPipelineUseCase {
+    build_novel: BuildNovelUseCase,
+    evaluate: EvaluateDailyContentUseCase,
+    sync: SyncUseCase,
+}
+impl DailyPipelineUseCase {
+    pub fn new(
+        build_novel: BuildNovelUseCase,
+        evaluate: EvaluateDailyContentUseCase,
+        sync: SyncUseCase,
+    ) -> Self {
+        Self {
+            build_novel,
+            evaluate,
+            sync,
+        }
+    }
+    pub fn session_date(task: &Task) -> Option<String> {
+        let stem = Path::new(task.file_paths.first()?).file_stem()?.to_str()?;
+        let date = stem.split('_').next()?;
+        NaiveDate::parse_from_str(date, "%Y%m%d").ok()?;
+        Some(date.to_string())
+    }
+    pub fn task_id(task_type: &str, date: &str) -> String {
+        format!("{}:{}", task_type, date)
+    }
+    pub fn plan(date: &str, sessions: Vec<String>) -> Vec<Task> {
+        let summary_path = SUMMARY_FILE_TEMPLATE.replace("{}", date);
+        let novel_path = NOVEL_FILE_TEMPLATE.replace("{}", date);
+        let day_over = NaiveDate::parse_from_str(date, "%Y%m%d")
+            .ok()
+            .and_then(|d| d.succ_opt())
+            .and_then(|d| {
+                Local
+                    .from_local_datetime(&d.and_hms_opt(0, 0, 0)?)
+                    .earliest()
+            })
+            .map(|t| t.with_timezone(&Utc) + Duration::seconds(DAILY_PIPELINE_DELAY_SECS));
+        let id = |task_type: &str| Self::task_id(task_type, date);
+        let task = |task_type: &str, file_paths: Vec<String>, depends_on: Vec<String>| Task {
+            id: id(task_type),
+            created_at: Utc::now(),
+            status: STATUS_PENDING.to_string(),
+            task_type: task_type.to_string(),
+            file_paths,
+            session_id: None,
+            profile: None,
+            lease_owner: None,
+            lease_expires_at: None,
+            attempts: 0,
+            last_error: None,
+            next_attempt_at: None,
+            depends_on,
+            date: Some(date.to_string()),
+        };
+        vec![
+            Task {
+                next_attempt_at: day_over,
+                ..task(TASK_TYPE_BUILD_NOVEL, vec![summary_path.clone()], sessions)
+            },
+            task(
+                TASK_TYPE_EVALUATE,
+                vec![summary_path, novel_path.clone()],
+                vec![id(TASK_TYPE_BUILD_NOVEL)],
+            ),
+            task(
+                TASK_TYPE_GENERATE_IMAGE,
+                vec![novel_path],
+                vec![id(TASK_TYPE_BUILD_NOVEL)],
+            ),
+            task(
+                TASK_TYPE_SYNC,
+                Vec::new(),
+                vec![id(TASK_TYPE_EVALUATE), id(TASK_TYPE_GENERATE_IMAGE)],
+            ),
+        ]
+    }
+    pub async fn execute(&self, task: &Task) -> anyhow::Result<()> {
+        let date = task
+            .date
+            .as_deref()
+            .ok_or_else(|| anyhow::anyhow!("Task {} has no date", task.id))?;
+        info!("Running {} for {}", task.task_type, date);
+        match task.task_type.as_str() {
+            TASK_TYPE_BUILD_NOVEL => {
+                self.build_novel.write_chapter(date).await?;
+            }
+            TASK_TYPE_EVALUATE => self.evaluate.execute(date).await?,
+            TASK_TYPE_GENERATE_IMAGE => {
+                let novel_path = NOVEL_FILE_TEMPLATE.replace("{}", date);
+                let novel = std::fs::read_to_string(&novel_path)
+                    .with_context(|| format!("Novel not found at {}", novel_path))?;
+                self.build_novel.generate_image(date, &novel).await;
+            }
+            TASK_TYPE_SYNC => self.sync.execute().await?,
+            other => anyhow::bail!("Unknown daily task type: {}", other),
+        }
+        Ok(())
+    }
+}
@@ -44,8 +44,17 @@ impl DoctorUseCase {
                 warn!("[WARN] Directory missing: {}", dir);
             }
         }
-        let _: Settings = Settings::new().unwrap();
+        let settings: Settings = Settings::new().unwrap();
         info!("[OK] Configuration (Settings) is valid");
+        if settings.audio_backend == crate::domain::CaptureBackend::Pulse {
+            let parec = Command::new(crate::domain::constants::PAREC_CMD)
+                .arg("--version")
+                .output();
+            match parec {
+                Ok(_) => info!("[OK] parec is installed"),
+                Err(_) => error!("[FAIL] parec not found in PATH (audio.backend: pulse)"),
+            }
+        }
         match crate::infrastructure::prompts::Prompts::load() {
             Ok(_) => info!("[OK] Prompts loaded successfully"),
             Err(e) => panic!("[FATAL] Prompts error: {}", e),
@@ -1,5 +1,6 @@
 use crate::domain::Curator;
 use crate::infrastructure::api::SupabaseClient;
+use anyhow::Context;
 use std::fs;
 use std::path::Path;
 use tracing::info;
@@ -11,21 +12,25 @@ impl EvaluateDailyContentUseCase {
     pub fn new(curator: Box<dyn Curator>, supabase: Option<SupabaseClient>) -> Self {
         Self { curator, supabase }
     }
-    pub async fn execute(&self, date: &str) {
+    pub async fn execute(&self, date: &str) -> anyhow::Result<()> {
         let summary_path = crate::domain::constants::SUMMARY_FILE_TEMPLATE.replace("{}", date);
         let novel_path = crate::domain::constants::NOVEL_FILE_TEMPLATE.replace("{}", date);
         if !Path::new(&summary_path).exists() || !Path::new(&novel_path).exists() {
-            panic!("Summary or Novel not found for {}", date);
+            anyhow::bail!("Summary or Novel not found for {}", date);
         }
-        let summary_text = fs::read_to_string(summary_path).unwrap();
-        let novel_text = fs::read_to_string(novel_path).unwrap();
+        let summary_text = fs::read_to_string(&summary_path)
+            .with_context(|| format!("Failed to read {}", summary_path))?;
+        let novel_text = fs::read_to_string(&novel_path)
+            .with_context(|| format!("Failed to read {}", novel_path))?;
         info!("Evaluating content for {}...", date);
-        let result = self.curator.evaluate(&summary_text, &novel_text).await;
+        let result = self.curator.evaluate(&summary_text, &novel_text).await?;
         let eval_path = crate::domain::constants::EVALUATION_FILE_TEMPLATE.replace("{}", date);
         if let Some(parent) = Path::new(&eval_path).parent() {
-            fs::create_dir_all(parent).unwrap();
+            fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create {}", parent.display()))?;
         }
-        fs::write(&eval_path, serde_json::to_string_pretty(&result).unwrap()).unwrap();
+        fs::write(&eval_path, serde_json::to_string_pretty(&result)?)
+            .with_context(|| format!("Failed to write {}", eval_path))?;
         info!("Evaluation saved to {}", eval_path);
         if let Some(ref supabase) = self.supabase {
             info!("Syncing evaluation to Supabase...");
@@ -35,7 +40,8 @@ impl EvaluateDailyContentUseCase {
                 "score": result.quality_score,
                 "reasoning": result.reasoning
             });
-            supabase.upsert("evaluations", &data).await.unwrap();
+            supabase.upsert("evaluations", &data).await?;
         }
+        Ok(())
     }
 }
@@ -1,11 +1,28 @@
 use crate::domain::{
-    AudioRecorder, ContentGenerator, Environment, FileWatcher, ProcessMonitor,
-    TaskRepository as TaskRepositoryTrait,
+    AudioRecorder, ContentGenerator, ControlCommand, ControlReply, ControlRequest, Environment,
+    FileWatcher, LifeEvent, MonitorStatus, ProcessMonitor, RecordingConfig, RecordingProfile,
+    RetryPolicy, SourceType, TaskConcurrency, TaskRepository as TaskRepositoryTrait,
 };
+use serde_json::json;
 use std::path::PathBuf;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 use tracing::{info, warn};
+#[derive(Default)]
+struct SessionState {
+    recording: bool,
+    manual: bool,
+    paused: bool,
+    suppressed: bool,
+    rearm: bool,
+    label: Option<String>,
+    session_id: Option<String>,
+    active_profile: Option<RecordingProfile>,
+    last_detected: Option<(String, String)>,
+    recording_started_at: Option<Instant>,
+    running_since: Option<Instant>,
+    stopped_since: Option<Instant>,
+}
 pub struct MonitorUseCase {
     audio_recorder: Arc<dyn AudioRecorder>,
     process_monitor: Arc<tokio::sync::Mutex<dyn ProcessMonitor>>,
@@ -15,14 +32,17 @@ pub struct MonitorUseCase {
     curator: Arc<dyn crate::domain::Curator>,
     watcher: Arc<dyn FileWatcher>,
     activity_sync: Arc<crate::use_cases::sync_activity::ActivitySyncUseCase>,
+    daily_pipeline: Arc<crate::use_cases::daily_pipeline::DailyPipelineUseCase>,
     event_repository: Arc<dyn crate::domain::EventRepository>,
     check_interval: u64,
     recording_dir: PathBuf,
-    audio_device: Option<String>,
-    silence_threshold: f32,
+    recording_config: RecordingConfig,
     start_debounce_secs: u64,
     stop_grace_secs: u64,
     min_recording_secs: u64,
+    retry_policy: RetryPolicy,
+    task_concurrency: TaskConcurrency,
+    control_rx: tokio::sync::Mutex<Option<tokio::sync::mpsc::Receiver<ControlRequest>>>,
 }
 impl MonitorUseCase {
     #[allow(clippy::too_many_arguments)]
@@ -35,14 +55,17 @@ impl MonitorUseCase {
         curator: Arc<dyn crate::domain::Curator>,
         watcher: Arc<dyn FileWatcher>,
         activity_sync: Arc<crate::use_cases::sync_activity::ActivitySyncUseCase>,
+        daily_pipeline: Arc<crate::use_cases::daily_pipeline::DailyPipelineUseCase>,
         event_repository: Arc<dyn crate::domain::EventRepository>,
         check_interval: u64,
         recording_dir: PathBuf,
-        audio_device: Option<String>,
-        silence_threshold: f32,
+        recording_config: RecordingConfig,
         start_debounce_secs: u64,
         stop_grace_secs: u64,
         min_recording_secs: u64,
+        retry_policy: RetryPolicy,
+        task_concurrency: TaskConcurrency,
+        control_rx: Option<tokio::sync::mpsc::Receiver<ControlRequest>>,
     ) -> Self {
         Self {
             audio_recorder,
@@ -53,14 +76,17 @@ impl MonitorUseCase {
             curator,
             watcher,
             activity_sync,
+            daily_pipeline,
             event_repository,
             check_interval,
             recording_dir,
-            audio_device,
-            silence_threshold,
+            recording_config,
             start_debounce_secs,
             stop_grace_secs,
             min_recording_secs,
+            retry_policy,
+            task_concurrency,
+            control_rx: tokio::sync::Mutex::new(control_rx),
         }
     }
     pub async fn execute(&self, spawn_worker: bool) {
@@ -76,16 +102,17 @@ impl MonitorUseCase {
                 self.event_repository.clone(),
                 self.curator.clone(),
                 self.activity_sync.clone(),
+                self.daily_pipeline.clone(),
+                self.retry_policy,
+                self.task_concurrency.clone(),
             ));
             tokio::spawn(async move { task_runner.run().await });
         } else {
             info!("Task worker disabled; monitor running in audio-only mode.");
         }
         tokio::spawn(async move { crate::use_cases::HealthMonitor::run().await });
-        let mut is_recording = false;
-        let mut recording_started_at: Option<Instant> = None;
-        let mut running_since: Option<Instant> = None;
-        let mut stopped_since: Option<Instant> = None;
+        let mut state = SessionState::default();
+        let mut control_rx = self.control_rx.lock().await.take();
 
         let (shutdown_tx, mut shutdown_rx) = tokio::sync::mpsc::channel(1);
         tokio::spawn(async move {
@@ -106,90 +133,162 @@ impl MonitorUseCase {
             let _ = shutdown_tx.send(()).await;
         });
 
+        if self.recording_config.backend.available_on_host()
+            && self.recording_config.pre_roll_secs > 0
+        {
+            self.audio_recorder.arm(self.recording_config.clone());
+        }
+
         info!("Monitor loop started. Press Ctrl+C to gracefully stop.");
 
         loop {
             tokio::select! {
                 _ = shutdown_rx.recv() => {
                     info!("Received shutdown signal. Committing active recording and exiting...");
-                    if is_recording {
-                        if let Some(path) = self.audio_recorder.stop() {
+                    if state.recording {
+                        if let Some(path) = self.stop_session(&mut state, "shutdown").await {
                             info!("Graceful shutdown saved to: {:?}", path);
-                            self.task_repository.add(
-                                "process_session",
-                                vec![path.to_string_lossy().to_string()],
-                            );
                         }
                     }
+                    self.audio_recorder.disarm();
                     std::process::exit(0);
                 }
+                Some(request) = async {
+                    match control_rx.as_mut() {
+                        Some(rx) => rx.recv().await,
+                        None => std::future::pending().await,
+                    }
+                } => {
+                    let reply = self.handle_control(&mut state, request.command).await;
+                    let _ = request.reply.send(reply);
+                }
                 _ = tokio::time::sleep(Duration::from_secs(self.check_interval)) => {
                     let now = Instant::now();
-                    let running = self.process_monitor.lock().await.is_running();
-                    if running {
-                        stopped_since = None;
-                        if !is_recording {
-                            if running_since.is_none() {
-                                running_since = Some(now);
+                    if state.recording {
+                        self.enqueue_finished_segments(&state.session_id, &state.active_profile).await;
+                    }
+                    self.record_device_events(&state.session_id).await;
+                    let detected = self.process_monitor.lock().await.detect();
+                    let current = detected
+                        .as_ref()
+                        .map(|d| (d.profile.name.clone(), d.process.clone()));
+                    let previous = state.last_detected.clone();
+                    if current != previous {
+                        let (kind, (profile, process)) = match (&previous, &current) {
+                            (None, Some(now_seen)) => ("ProcessDetected", now_seen),
+                            (Some(_), Some(now_seen)) => ("ProcessChanged", now_seen),
+                            (Some(was_seen), None) => ("ProcessLost", was_seen),
+                            (None, None) => unreachable!(),
+                        };
+                        self.record_event(
+                            SourceType::UbuntuMonitor,
+                            json!({
+                                "type": kind,
+                                "profile": profile,
+                                "process": process,
+                                "session_id": state.session_id,
+                            }),
+                        )
+                        .await;
+                        state.last_detected = current;
+                    }
+                    if let Some(detected) = detected {
+                        if state.recording && state.stopped_since.is_some() {
+                            self.record_event(
+                                SourceType::System,
+                                json!({
+                                    "type": "GraceCancelled",
+                                    "session_id": state.session_id,
+                                    "profile": state.active_profile.as_ref().map(|p| &p.name),
+                                }),
+                            )
+                            .await;
+                        }
+                        state.stopped_since = None;
+                        if !state.recording && !state.paused && !state.suppressed {
+                            let profile = detected.profile;
+                            let start_debounce_secs = profile
+                                .start_debounce_secs
+                                .unwrap_or(self.start_debounce_secs);
+                            if state.running_since.is_none() {
+                                state.running_since = Some(now);
                                 info!(
-                                    "Start trigger pending: waiting {}s debounce",
-                                    self.start_debounce_secs
+                                    "Start trigger pending for profile '{}': waiting {}s debounce",
+                                    profile.name, start_debounce_secs
                                 );
+                                self.record_event(
+                                    SourceType::System,
+                                    json!({
+                                        "type": "DebounceStarted",
+                                        "profile": profile.name,
+                                        "process": detected.process,
+                                        "debounce_secs": start_debounce_secs,
+                                    }),
+                                )
+                                .await;
                             }
-                            if let Some(since) = running_since {
-                                if now.duration_since(since).as_secs() >= self.start_debounce_secs {
-                                    let timestamp =
-                                        chrono::Local::now().format("%Y%m%d_%H%M%S").to_string();
-                                    let path = self.recording_dir.join(format!("{}.wav", timestamp));
-                                    if cfg!(target_os = "windows") {
-                                        self.audio_recorder.start(
-                                            path,
-                                            crate::domain::constants::DEFAULT_SAMPLE_RATE,
-                                            crate::domain::constants::DEFAULT_CHANNELS,
-                                            self.audio_device.clone(),
-                                            self.silence_threshold,
-                                        );
-                                        is_recording = true;
-                                        recording_started_at = Some(now);
-                                        running_since = None;
-                                        info!("Recording started.");
-                                    } else {
-                                        info!("Recording skipped (non-Windows host)");
-                                        running_since = None;
-                                    }
+                            if let Some(since) = state.running_since {
+                                if now.duration_since(since).as_secs() >= start_debounce_secs {
+                                    state.running_since = None;
+                                    let _ = self
+                                        .start_session(&mut state, profile, Some(&detected.process))
+                                        .await;
                                 }
                             }
                         }
                     } else {
-                        running_since = None;
-                        if is_recording {
-                            if stopped_since.is_none() {
-                                stopped_since = Some(now);
+                        state.suppressed = false;
+                        if let Some(since) = state.running_since.take() {
+                            self.record_event(
+                                SourceType::System,
+                                json!({
+                                    "type": "DebounceCancelled",
+                                    "profile": previous.as_ref().map(|(profile, _)| profile),
+                                    "waited_secs": now.duration_since(since).as_secs(),
+                                }),
+                            )
+                            .await;
+                        }
+                        if state.recording && !state.manual {
+                            let stop_grace_secs = state
+                                .active_profile
+                                .as_ref()
+                                .and_then(|p| p.stop_grace_secs)
+                                .unwrap_or(self.stop_grace_secs);
+                            let min_recording_secs = state
+                                .active_profile
+                                .as_ref()
+                                .and_then(|p| p.min_recording_secs)
+                                .unwrap_or(self.min_recording_secs);
+                            if state.stopped_since.is_none() {
+                                state.stopped_since = Some(now);
                                 info!(
                                     "Stop trigger pending: waiting {}s grace and {}s min-duration",
-                                    self.stop_grace_secs, self.min_recording_secs
+                                    stop_grace_secs, min_recording_secs
                                 );
+                                self.record_event(
+                                    SourceType::System,
+                                    json!({
+                                        "type": "GraceStarted",
+                                        "session_id": state.session_id,
+                                        "profile": state.active_profile.as_ref().map(|p| &p.name),
+                                        "stop_grace_secs": stop_grace_secs,
+                                        "min_recording_secs": min_recording_secs,
+                                    }),
+                                )
+                                .await;
                             }
-                            let grace_elapsed = stopped_since.is_some_and(|since| {
-                                now.duration_since(since).as_secs() >= self.stop_grace_secs
+                            let grace_elapsed = state.stopped_since.is_some_and(|since| {
+                                now.duration_since(since).as_secs() >= stop_grace_secs
                             });
-                            let min_elapsed = recording_started_at.is_some_and(|since| {
-                                now.duration_since(since).as_secs() >= self.min_recording_secs
+                            let min_elapsed = state.recording_started_at.is_some_and(|since| {
+                                now.duration_since(since).as_secs() >= min_recording_secs
                             });
                             if grace_elapsed && min_elapsed {
-                                match self.audio_recorder.stop() {
-                                    Some(path) => {
-                                        info!("Session recording saved to: {:?}", path);
-                                        self.task_repository.add(
-                                            "process_session",
-                                            vec![path.to_string_lossy().to_string()],
-                                        );
-                                    }
+                                match self.stop_session(&mut state, "process_lost").await {
+                                    Some(path) => info!("Session recording saved to: {:?}", path),
                                     None => warn!("Recorder stopped, but no output path returned"),
                                 }
-                                is_recording = false;
-                                recording_started_at = None;
-                                stopped_since = None;
                             }
                         }
                     }
@@ -198,6 +297,297 @@ impl MonitorUseCase {
         }
     }
 
+    async fn start_session(
+        &self,
+        state: &mut SessionState,
+        profile: RecordingProfile,
+        process: Option<&str>,
+    ) -> Result<String, String> {
+        if !self.recording_config.backend.available_on_host() {
+            let reason = format!(
+                "{:?} backend unavailable on this host",
+                self.recording_config.backend
+            );
+            info!("Recording skipped ({})", reason);
+            self.record_event(
+                SourceType::System,
+                json!({
+                    "type": "RecordingSkipped",
+                    "profile": profile.name,
+                    "process": process,
+                    "reason": reason,
+                }),
+            )
+            .await;
+            return Err(reason);
+        }
+        let timestamp = chrono::Local::now().format("%Y%m%d_%H%M%S").to_string();
+        let path = self.recording_dir.join(format!(
+            "{}.{}",
+            timestamp,
+            self.recording_config.format.extension()
+        ));
+        let id = uuid::Uuid::now_v7().to_string();
+        let mut config = profile.apply(&self.recording_config);
+        config.session_id = Some(id.clone());
+        if self.recording_config.pre_roll_secs > 0
+            && config.sources != self.recording_config.sources
+        {
+            info!(
+                "Profile '{}' uses its own sources; releasing the pre-roll capture",
+                profile.name
+            );
+            self.audio_recorder.disarm();
+            state.rearm = true;
+        }
+        if let Err(reason) = self.audio_recorder.start(path.clone(), config) {
+            warn!("Recording failed to start ({})", reason);
+            self.record_event(
+                SourceType::System,
+                json!({
+                    "type": "RecordingFailed",
+                    "profile": profile.name,
+                    "process": process,
+                    "reason": reason,
+                }),
+            )
+            .await;
+            return Err(reason);
+        }
+        info!(
+            "Recording session {} started (profile '{}').",
+            id, profile.name
+        );
+        self.record_event(
+            SourceType::System,
+            json!({
+                "type": "RecordingStarted",
+                "session_id": id,
+                "profile": profile.name,
+                "process": process,
+                "manual": state.manual,
+                "label": state.label,
+                "path": path.to_string_lossy(),
+            }),
+        )
+        .await;
+        state.session_id = Some(id.clone());
+        state.active_profile = Some(profile);
+        state.recording = true;
+        state.recording_started_at = Some(Instant::now());
+        Ok(id)
+    }
+
+    async fn stop_session(&self, state: &mut SessionState, reason: &str) -> Option<PathBuf> {
+        let last = self.audio_recorder.stop();
+        self.enqueue_finished_segments(&state.session_id, &state.active_profile)
+            .await;
+        if let Some(path) = &last {
+            self.enqueue_recording(path, &state.session_id, &state.active_profile)
+                .await;
+        }
+        self.record_event(
+            SourceType::System,
+            json!({
+                "type": "RecordingStopped",
+                "reason": reason,
+                "session_id": state.session_id,
+                "profile": state.active_profile.as_ref().map(|p| &p.name),
+                "manual": state.manual,
+                "label": state.label,
+                "path": last.as_ref().map(|p| p.to_string_lossy()),
+                "duration_secs": state.recording_started_at.map(|t| t.elapsed().as_secs()),
+            }),
+        )
+        .await;
+        if std::mem::take(&mut state.rearm) {
+            self.audio_recorder.arm(self.recording_config.clone());
+        }
+        state.recording = false;
+        state.manual = false;
+        state.label = None;
+        state.session_id = None;
+        state.active_profile = None;
+        state.recording_started_at = None;
+        state.stopped_since = None;
+        last
+    }
+
+    async fn handle_control(
+        &self,
+        state: &mut SessionState,
+        command: ControlCommand,
+    ) -> ControlReply {
+        info!("Control command received: {:?}", command);
+        match command {
+            ControlCommand::Start { label } => {
+                if state.recording && state.manual {
+                    return ControlReply::error(format!(
+                        "Session {} is already recording manually",
+                        state.session_id.as_deref().unwrap_or_default()
+                    ));
+                }
+                state.manual = true;
+                state.label = label;
+                if state.recording {
+                    state.stopped_since = None;
+                    self.record_event(
+                        SourceType::System,
+                        json!({
+                            "type": "RecordingPinned",
+                            "session_id": state.session_id,
+                            "profile": state.active_profile.as_ref().map(|p| &p.name),
+                            "label": state.label,
+                        }),
+                    )
+                    .await;
+                    return ControlReply::ok(format!(
+                        "Session {} is now held open until `stop`",
+                        state.session_id.as_deref().unwrap_or_default()
+                    ));
+                }
+                state.running_since = None;
+                let profile = RecordingProfile {
+                    name: crate::domain::constants::MANUAL_PROFILE_NAME.to_string(),
+                    processes: Vec::new(),
+                    rule: None,
+                    sources: Vec::new(),
+                    silence_threshold: None,
+                    start_debounce_secs: None,
+                    stop_grace_secs: None,
+                    min_recording_secs: None,
+                    summary_prompt: None,
+                    tags: state.label.iter().cloned().collect(),
+                };
+                match self.start_session(state, profile, None).await {
+                    Ok(id) => ControlReply::ok(format!("Recording session {} started", id)),
+                    Err(reason) => {
+                        state.manual = false;
+                        state.label = None;
+                        ControlReply::error(format!("Recording skipped: {}", reason))
+                    }
+                }
+            }
+            ControlCommand::Stop => {
+                if !state.recording {
+                    return ControlReply::error("Not recording");
+                }
+                state.suppressed = state.last_detected.is_some();
+                if state.suppressed {
+                    info!("Auto-trigger held off until the detected process exits");
+                }
+                let id = state.session_id.clone().unwrap_or_default();
+                match self.stop_session(state, "manual").await {
+                    Some(path) => {
+                        info!("Session recording saved to: {:?}", path);
+                        ControlReply::ok(format!(
+                            "Recording session {} saved to {}",
+                            id,
+                            path.to_string_lossy()
+                        ))
+                    }
+                    None => ControlReply::ok(format!("Recording session {} stopped", id)),
+                }
+            }
+            ControlCommand::Mark { note } => {
+                self.record_event(
+                    SourceType::System,
+                    json!({
+                        "type": "Mark",
+                        "note": note,
+                        "session_id": state.session_id,
+                        "label": state.label,
+                        "offset_secs": state.recording_started_at.map(|t| t.elapsed().as_secs()),
+                    }),
+                )
+                .await;
+                ControlReply::ok("Mark recorded")
+            }
+            ControlCommand::Pause => {
+                state.paused = true;
+                state.running_since = None;
+                self.record_event(SourceType::System, json!({ "type": "TriggerPaused" }))
+                    .await;
+                ControlReply::ok("Auto-trigger paused")
+            }
+            ControlCommand::Resume => {
+                state.paused = false;
+                state.suppressed = false;
+                self.record_event(SourceType::System, json!({ "type": "TriggerResumed" }))
+                    .await;
+                ControlReply::ok("Auto-trigger resumed")
+            }
+            ControlCommand::Status => ControlReply {
+                status: Some(MonitorStatus {
+                    recording: state.recording,
+                    manual: state.manual,
+                    paused: state.paused,
+                    session_id: state.session_id.clone(),
+                    profile: state.active_profile.as_ref().map(|p| p.name.clone()),
+                    label: state.label.clone(),
+                    elapsed_secs: state.recording_started_at.map(|t| t.elapsed().as_secs()),
+                    detected: state
+                        .last_detected
+                        .as_ref()
+                        .map(|(profile, process)| format!("{} ({})", process, profile)),
+                }),
+                ..ControlReply::ok(if state.recording { "recording" } else { "idle" })
+            },
+        }
+    }
+
+    async fn enqueue_finished_segments(
+        &self,
+        session_id: &Option<String>,
+        profile: &Option<RecordingProfile>,
+    ) {
+        for path in self.audio_recorder.take_finished_segments() {
+            info!("Recording segment ready: {:?}", path);
+            self.enqueue_recording(&path, session_id, profile).await;
+        }
+    }
+
+    async fn record_event(&self, source: SourceType, payload: serde_json::Value) {
+        self.event_repository
+            .save(&LifeEvent::new(source, payload))
+            .await;
+    }
+
+    async fn record_device_events(&self, session_id: &Option<String>) {
+        for event in self.audio_recorder.take_device_events() {
+            match event.kind {
+                crate::domain::DeviceEventKind::Lost => warn!(
+                    "Audio source '{}' lost ({}); recording continues with silence",
+                    event.source, event.detail
+                ),
+                _ => info!(
+                    "Audio source '{}' {:?} on {:?}",
+                    event.source, event.kind, event.device
+                ),
+            }
+            let mut payload = serde_json::to_value(&event).unwrap();
+            payload["type"] = serde_json::json!("AudioDevice");
+            payload["session_id"] = serde_json::json!(session_id);
+            self.record_event(SourceType::AudioDevice, payload).await;
+        }
+    }
+
+    async fn enqueue_recording(
+        &self,
+        path: &std::path::Path,
+        session_id: &Option<String>,
+        profile: &Option<RecordingProfile>,
+    ) {
+        self.task_repository
+            .add_with_profile(
+                crate::domain::constants::TASK_TYPE_PROCESS_SESSION,
+                vec![path.to_string_lossy().to_string()],
+                session_id.clone(),
+                profile.as_ref().map(RecordingProfile::task_profile),
+            )
+            .await;
+    }
+
     async fn recover_partial_recordings(&self) {
         info!("Scanning for orphaned partial recordings...");
         if let Ok(entries) = std::fs::read_dir(&self.recording_dir) {
@@ -205,21 +595,82 @@ impl MonitorUseCase {
                 let path = entry.path();
                 if path.is_file() {
                     let file_name = path.file_name().unwrap_or_default().to_string_lossy();
-                    if file_name.ends_with(".wav.part") || file_name.ends_with(".flac.part") {
-                        match std::fs::rename(&path, path.with_extension("")) {
-                            Ok(_) => {
-                                let recovered = path.with_extension("");
-                                info!("Recovered orphaned recording: {:?}", recovered);
-                                self.task_repository.add(
-                                    "process_session",
-                                    vec![recovered.to_string_lossy().to_string()],
-                                );
-                            }
-                            Err(e) => warn!("Failed to recover {:?}: {}", path, e),
-                        }
+                    if file_name.ends_with(crate::domain::constants::WAV_PART_EXTENSION)
+                        || file_name.ends_with(crate::domain::constants::FLAC_PART_EXTENSION)
+                    {
+                        self.recover_partial_recording(&path).await;
                     }
                 }
             }
         }
     }
+
+    async fn recover_partial_recording(&self, path: &std::path::Path) {
+        use crate::infrastructure::audio::RepairOutcome;
+        let outcome = crate::infrastructure::audio::repair_recording(path);
+        let payload = match &outcome {
+            RepairOutcome::Unrecoverable(reason) => {
+                let corrupt_dir = self
+                    .recording_dir
+                    .join(crate::domain::constants::CORRUPT_RECORDINGS_DIR);
+                if let Err(e) = std::fs::create_dir_all(&corrupt_dir) {
+                    warn!(
+                        "Failed to create quarantine directory {:?} for {:?}: {}",
+                        corrupt_dir, path, e
+                    );
+                    return;
+                }
+                let target = corrupt_dir.join(path.file_name().unwrap_or_default());
+                if let Err(e) = std::fs::rename(path, &target) {
+                    warn!("Failed to quarantine {:?}: {}", path, e);
+                    return;
+                }
+                warn!(
+                    "Quarantined unrecoverable recording {:?}: {}",
+                    target, reason
+                );
+                serde_json::json!({
+                    "type": "RecordingQuarantined",
+                    "path": path.to_string_lossy(),
+                    "quarantined_to": target.to_string_lossy(),
+                    "reason": reason,
+                })
+            }
+            RepairOutcome::Intact | RepairOutcome::Repaired { .. } => {
+                let recovered = path.with_extension("");
+                if let Err(e) = std::fs::rename(path, &recovered) {
+                    warn!("Failed to recover {:?}: {}", path, e);
+                    return;
+                }
+                let (data_bytes, trimmed_bytes) = match outcome {
+                    RepairOutcome::Repaired {
+                        data_bytes,
+                        trimmed_bytes,
+                    } => {
+                        info!(
+                            "Repaired orphaned recording {:?}: {} data bytes, {} trimmed",
+                            recovered, data_bytes, trimmed_bytes
+                        );
+                        (Some(data_bytes), trimmed_bytes)
+                    }
+                    _ => {
+                        info!("Recovered orphaned recording: {:?}", recovered);
+                        (None, 0)
+                    }
+                };
+                let session_id = crate::infrastructure::audio::SegmentSidecar::load(&recovered)
+                    .and_then(|s| s.session_id);
+                self.enqueue_recording(&recovered, &session_id, &None).await;
+                serde_json::json!({
+                    "type": "RecordingRecovered",
+                    "path": recovered.to_string_lossy(),
+                    "repaired": data_bytes.is_some(),
+                    "data_bytes": data_bytes,
+                    "trimmed_bytes": trimmed_bytes,
+                    "session_id": session_id,
+                })
+            }
+        };
+        self.record_event(SourceType::System, payload).await;
+    }
 }
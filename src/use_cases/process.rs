@@ -1,5 +1,6 @@
 use crate::domain::{ContentGenerator, Task};
 use crate::use_cases::transcode::TranscodeUseCase;
+use anyhow::Context;
 use std::path::Path;
 use std::sync::Arc;
 use tracing::info;
@@ -21,11 +22,24 @@ impl ProcessUseCase {
         }
     }
 
-    pub async fn execute_session(&self, task: &Task) {
+    pub async fn execute_session(&self, task: &Task) -> anyhow::Result<()> {
         let transcoder = TranscodeUseCase::new();
+        if let Some(session_id) = &task.session_id {
+            info!(
+                "Task {} belongs to recording session {}",
+                task.id, session_id
+            );
+        }
+        if let Some(profile) = &task.profile {
+            info!("Task {} uses recording profile '{}'", task.id, profile.name);
+        }
+        let variant = task
+            .profile
+            .as_ref()
+            .and_then(|p| p.summary_prompt.as_deref());
         for file_path in &task.file_paths {
-            info!("Transcribing {} (via Gemini)...", file_path);
-            let transcript = self.gemini.transcribe(file_path).await;
+            info!("Transcribing {}...", file_path);
+            let transcript = self.gemini.transcribe(file_path).await?;
             info!("Preprocessing transcript (Rust)...");
             let preprocessor = crate::infrastructure::preprocessor::TranscriptPreprocessor::new();
             let cleaned = preprocessor.process(&transcript);
@@ -45,8 +59,9 @@ impl ProcessUseCase {
             )
             .map(|dt| chrono::DateTime::<chrono::Utc>::from_naive_utc_and_offset(dt, chrono::Utc))
             .ok();
+            let sidecar = crate::infrastructure::audio::SegmentSidecar::load(path);
 
-            if start_time.is_none() {
+            if start_time.is_none() && sidecar.is_none() {
                 tracing::warn!(
                     "Skipping summary/activity overlay for {} (unsupported format)",
                     file_path
@@ -55,41 +70,78 @@ impl ProcessUseCase {
                 // For now, let's just use dummy time for summary if we really want to process it,
                 // but the current logic highly depends on start_time for activity overlay.
             }
-            let start_time = start_time.unwrap_or_else(chrono::Utc::now);
-            let end_time = start_time + chrono::Duration::minutes(30);
+            let start_time = sidecar
+                .as_ref()
+                .map(|s| s.part_started_at())
+                .or(start_time)
+                .unwrap_or_else(chrono::Utc::now);
+            let end_time = sidecar
+                .as_ref()
+                .map(|s| s.ended_at())
+                .unwrap_or(start_time + chrono::Duration::minutes(30));
             let activities = self
                 .event_repository
                 .find_by_timerange(start_time, end_time)
                 .await;
-            let mut activity_context = String::new();
+            let mut timeline = Vec::new();
             for event in activities {
-                if let crate::domain::SourceType::WindowsActivity = event.source {
-                    activity_context.push_str(&format!(
-                        "[{}] {:?}\n",
-                        event.timestamp.format("%H:%M:%S"),
-                        event.payload
+                let overlay = matches!(
+                    event.source,
+                    crate::domain::SourceType::WindowsActivity
+                        | crate::domain::SourceType::UbuntuMonitor
+                ) || event.payload["type"] == "Mark";
+                if overlay {
+                    timeline.push((event.timestamp, format!("{:?}", event.payload)));
+                }
+            }
+            if let Some(sidecar) = &sidecar {
+                for segment in &sidecar.segments {
+                    timeline.push((
+                        sidecar.wall_clock_at(segment.file_start_ms),
+                        format!(
+                            "Speech (audio {} - {})",
+                            audio_offset(segment.file_start_ms),
+                            audio_offset(segment.file_end_ms)
+                        ),
                     ));
                 }
             }
+            timeline.sort_by_key(|(at, _)| *at);
+            let mut activity_context = String::new();
+            for (at, line) in timeline {
+                activity_context.push_str(&format!("[{}] {}\n", at.format("%H:%M:%S"), line));
+            }
             info!("Summarizing transcript with activity overlay...");
             let summary = self
                 .curator
-                .summarize_session(&cleaned, &activity_context)
-                .await;
+                .summarize_session(&cleaned, &activity_context, variant)
+                .await?;
             info!("Verifying summary accuracy (Self-Consistency)...");
-            let verify_result = self
+            match self
                 .curator
                 .verify_summary(&summary, &cleaned, &activity_context)
-                .await;
-            info!(
-                "Summary Verification: Score={}, Reason={}",
-                verify_result.faithfulness_score, verify_result.reasoning
-            );
+                .await
+            {
+                Ok(verify_result) => info!(
+                    "Summary Verification: Score={}, Reason={}",
+                    verify_result.faithfulness_score, verify_result.reasoning
+                ),
+                Err(e) => tracing::warn!(
+                    "Summary verification unavailable for {} ({}); keeping unverified summary",
+                    file_path,
+                    e
+                ),
+            }
 
             let summary_out_path =
                 crate::domain::constants::SUMMARY_FILE_TEMPLATE.replace("{}", date_str);
+            let summary = match task.profile.as_ref().filter(|p| !p.tags.is_empty()) {
+                Some(profile) => format!("tags: {:?}\n\n{summary}", profile.tags),
+                None => summary,
+            };
             let daily_summary = if Path::new(&summary_out_path).exists() {
-                let existing = std::fs::read_to_string(&summary_out_path).unwrap();
+                let existing = std::fs::read_to_string(&summary_out_path)
+                    .with_context(|| format!("Failed to read {}", summary_out_path))?;
                 format!("{existing}\n\n---\n\n## {stem}\n\n{summary}")
             } else {
                 format!("## {stem}\n\n{summary}")
@@ -97,12 +149,10 @@ impl ProcessUseCase {
             crate::infrastructure::fs_utils::atomic_write(&summary_out_path, daily_summary);
             info!("Daily summary refreshed at {}", summary_out_path);
 
-            let is_lossless_or_raw = Path::new(&file_path)
+            let is_lossless_or_raw = path
                 .extension()
-                .unwrap()
-                .to_str()
-                .map(|ext| matches!(ext.to_ascii_lowercase().as_str(), "wav" | "flac"))
-                .unwrap();
+                .and_then(|ext| ext.to_str())
+                .is_some_and(|ext| matches!(ext.to_ascii_lowercase().as_str(), "wav" | "flac"));
             if is_lossless_or_raw {
                 match transcoder.execute(file_path).await {
                     Ok(opus_path) => info!("Archived recording as {}", opus_path),
@@ -115,5 +165,10 @@ impl ProcessUseCase {
             }
         }
         info!("Session processing finished for task {}", task.id);
+        Ok(())
     }
 }
+fn audio_offset(ms: u64) -> String {
+    let secs = ms / 1000;
+    format!("{:02}:{:02}:{:02}", secs / 3600, secs / 60 % 60, secs % 60)
+}
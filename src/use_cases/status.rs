@@ -18,9 +18,13 @@ impl StatusUseCase {
     pub async fn execute(&self) {
         let now = Utc::now();
         let since = now - Duration::hours(24);
-        let repo =
-            TaskRepository::new(crate::infrastructure::settings::Settings::default_tasks_path());
-        let tasks = repo.load();
+        let settings =
+            crate::infrastructure::settings::Settings::new_allow_missing_gemini().unwrap();
+        let repo = TaskRepository::new(
+            crate::infrastructure::db::connect(&settings.db_path.to_string_lossy()).await,
+        );
+        repo.import_legacy().await;
+        let tasks = repo.load().await;
         let pending_count = tasks
             .iter()
             .filter(|t| t.status == crate::domain::constants::STATUS_PENDING)
@@ -29,6 +33,10 @@ impl StatusUseCase {
             .iter()
             .filter(|t| t.status == crate::domain::constants::STATUS_PROCESSING)
             .count();
+        let failed_count = tasks
+            .iter()
+            .filter(|t| t.status == crate::domain::constants::STATUS_FAILED)
+            .count();
         let completed_24h = tasks.iter().filter(|t| t.created_at >= since).count();
         let recordings_24h =
             self.count_recent_files(crate::domain::constants::RECORDINGS_DIR, since.timestamp());
@@ -39,6 +47,7 @@ impl StatusUseCase {
         info!("Tasks completed/created: {}", completed_24h);
         info!("Pending tasks: {}", pending_count);
         info!("Processing tasks: {}", processing_count);
+        info!("Failed tasks: {}", failed_count);
     }
     fn count_recent_files(&self, dir: &str, since_ts: i64) -> usize {
         let path = Path::new(dir);
@@ -1,61 +1,303 @@
 use crate::domain::constants::{
-    STATUS_COMPLETED, STATUS_PENDING, STATUS_PROCESSING, TASK_LOOP_INTERVAL_SECS,
-    TASK_TYPE_PROCESS_SESSION, TASK_TYPE_SYNC_ACTIVITY,
+    DAILY_TASK_TYPES, STATUS_CANCELLED, STATUS_COMPLETED, STATUS_FAILED, STATUS_PENDING,
+    TASK_HEARTBEAT_SECS, TASK_LEASE_EXPIRED_ERROR, TASK_LEASE_SECS, TASK_LOOP_INTERVAL_SECS,
+    TASK_TYPES, TASK_TYPE_BUILD_NOVEL, TASK_TYPE_PROCESS_SESSION, TASK_TYPE_SYNC_ACTIVITY,
 };
-use crate::domain::{ContentGenerator, Curator, TaskRepository};
+use crate::domain::{
+    ContentGenerator, Curator, LlmError, RetryPolicy, Task, TaskConcurrency, TaskRepository,
+};
+use crate::use_cases::daily_pipeline::DailyPipelineUseCase;
 use crate::use_cases::process::ProcessUseCase;
 use crate::use_cases::sync_activity::ActivitySyncUseCase;
+use std::collections::HashMap;
 use std::sync::Arc;
 use std::time::Duration;
+use tokio::task::JoinSet;
 use tokio::time::sleep;
-use tracing::{info, warn};
+use tracing::{error, info, warn};
+#[derive(Clone)]
 pub struct TaskRunner {
     repository: Arc<dyn TaskRepository>,
-    process_use_case: ProcessUseCase,
+    process_use_case: Arc<ProcessUseCase>,
     activity_sync: Arc<ActivitySyncUseCase>,
+    daily_pipeline: Arc<DailyPipelineUseCase>,
+    retry_policy: RetryPolicy,
+    concurrency: TaskConcurrency,
 }
 impl TaskRunner {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         gemini: Arc<dyn ContentGenerator>,
         repository: Arc<dyn TaskRepository>,
         event_repo: Arc<dyn crate::domain::EventRepository>,
         curator: Arc<dyn Curator>,
         activity_sync: Arc<ActivitySyncUseCase>,
+        daily_pipeline: Arc<DailyPipelineUseCase>,
+        retry_policy: RetryPolicy,
+        concurrency: TaskConcurrency,
     ) -> Self {
-        let process_use_case = ProcessUseCase::new(gemini, event_repo, curator);
+        let process_use_case = Arc::new(ProcessUseCase::new(gemini, event_repo, curator));
         Self {
             repository,
             process_use_case,
             activity_sync,
+            daily_pipeline,
+            retry_policy,
+            concurrency,
         }
     }
     pub async fn run(&self) {
+        let worker_id = format!(
+            "{}:{}",
+            sysinfo::System::host_name().unwrap_or_default(),
+            std::process::id()
+        );
+        info!(
+            "Task worker {} started (concurrency={:?}, llm_calls={})",
+            worker_id, self.concurrency.per_type, self.concurrency.llm_calls
+        );
+        let mut jobs = JoinSet::new();
+        let mut running: HashMap<tokio::task::Id, String> = HashMap::new();
         loop {
-            let tasks = self.repository.load();
-            for task in tasks {
-                if task.status == STATUS_PENDING {
-                    self.repository.update_status(&task.id, STATUS_PROCESSING);
-                    info!("Processing task: {} ({})", task.id, task.task_type);
-                    match task.task_type.as_str() {
-                        TASK_TYPE_PROCESS_SESSION => {
-                            self.process_use_case.execute_session(&task).await;
-                            self.repository.update_status(&task.id, STATUS_COMPLETED);
-                            info!("Task completed: {}", task.id);
-                        }
-                        TASK_TYPE_SYNC_ACTIVITY => {
-                            for file in &task.file_paths {
-                                self.activity_sync.execute(file).await;
-                            }
-                            self.repository.update_status(&task.id, STATUS_COMPLETED);
-                            info!("Task completed: {}", task.id);
-                        }
-                        _ => {
-                            warn!("Unknown task type: {}", task.task_type);
+            for task in self.repository.expired().await {
+                if self
+                    .fail(&task, &anyhow::anyhow!(TASK_LEASE_EXPIRED_ERROR), None)
+                    .await
+                {
+                    warn!(
+                        "Reclaimed task {} ({}) after its lease expired",
+                        task.id, task.task_type
+                    );
+                }
+            }
+            loop {
+                let saturated = self.saturated_types(&running);
+                let Some(task) = self
+                    .repository
+                    .claim(&worker_id, TASK_LEASE_SECS, &saturated)
+                    .await
+                else {
+                    break;
+                };
+                info!(
+                    "Processing task: {} ({}, attempt {}/{})",
+                    task.id, task.task_type, task.attempts, self.retry_policy.max_attempts
+                );
+                let task_type = task.task_type.clone();
+                let runner = self.clone();
+                let worker_id = worker_id.clone();
+                let handle = jobs.spawn(async move { runner.process(task, worker_id).await });
+                running.insert(handle.id(), task_type);
+            }
+            tokio::select! {
+                Some(done) = jobs.join_next_with_id(), if !jobs.is_empty() => {
+                    let id = match done {
+                        Ok((id, ())) => id,
+                        Err(e) => {
+                            error!("Task job aborted: {}", e);
+                            e.id()
                         }
+                    };
+                    running.remove(&id);
+                }
+                _ = self.repository.wait_for_task(Duration::from_secs(TASK_LOOP_INTERVAL_SECS)) => {}
+            }
+        }
+    }
+    fn saturated_types(&self, running: &HashMap<tokio::task::Id, String>) -> Vec<String> {
+        let mut counts: HashMap<&str, usize> = HashMap::new();
+        for task_type in running.values() {
+            *counts.entry(task_type).or_default() += 1;
+        }
+        let mut types: Vec<&str> = TASK_TYPES.to_vec();
+        types.extend(self.concurrency.per_type.keys().map(String::as_str));
+        types.extend(counts.keys());
+        types.sort_unstable();
+        types.dedup();
+        types
+            .into_iter()
+            .filter(|t| counts.get(t).copied().unwrap_or(0) >= self.concurrency.limit(t))
+            .map(str::to_string)
+            .collect()
+    }
+    async fn process(&self, task: Task, worker_id: String) {
+        let heartbeat = {
+            let repository = self.repository.clone();
+            let id = task.id.clone();
+            let worker_id = worker_id.clone();
+            tokio::spawn(async move {
+                loop {
+                    sleep(Duration::from_secs(TASK_HEARTBEAT_SECS)).await;
+                    if !repository.heartbeat(&id, &worker_id, TASK_LEASE_SECS).await {
+                        warn!("Lost lease on task {}", id);
+                        break;
+                    }
+                }
+            })
+        };
+        if task.task_type == TASK_TYPE_PROCESS_SESSION {
+            self.schedule_daily_pipeline(&task).await;
+        }
+        let result = self.execute(&task).await;
+        heartbeat.abort();
+        match result {
+            Ok(()) => {
+                if !self
+                    .repository
+                    .update_status(&task.id, STATUS_COMPLETED, Some(&worker_id))
+                    .await
+                {
+                    warn!(
+                        "Task {} is no longer leased by {}; completion discarded",
+                        task.id, worker_id
+                    );
+                    return;
+                }
+                info!("Task completed: {}", task.id);
+                if task.task_type == TASK_TYPE_PROCESS_SESSION {
+                    self.schedule_daily_pipeline(&task).await;
+                }
+            }
+            Err(e) => {
+                self.fail(&task, &e, Some(&worker_id)).await;
+            }
+        }
+    }
+    async fn execute(&self, task: &Task) -> anyhow::Result<()> {
+        match task.task_type.as_str() {
+            TASK_TYPE_PROCESS_SESSION => self.process_use_case.execute_session(task).await,
+            TASK_TYPE_SYNC_ACTIVITY => {
+                for file in &task.file_paths {
+                    self.activity_sync.execute(file).await;
+                }
+                Ok(())
+            }
+            t if DAILY_TASK_TYPES.contains(&t) => self.daily_pipeline.execute(task).await,
+            other => Err(anyhow::anyhow!("Unknown task type: {}", other)),
+        }
+    }
+    async fn schedule_daily_pipeline(&self, task: &Task) {
+        let Some(date) = DailyPipelineUseCase::session_date(task) else {
+            return;
+        };
+        let sessions: Vec<Task> = self
+            .repository
+            .load()
+            .await
+            .into_iter()
+            .filter(|t| {
+                t.task_type == TASK_TYPE_PROCESS_SESSION
+                    && DailyPipelineUseCase::session_date(t).as_deref() == Some(date.as_str())
+            })
+            .collect();
+        if let Some(blocked) = sessions
+            .iter()
+            .find(|t| t.status == STATUS_FAILED || t.status == STATUS_CANCELLED)
+        {
+            warn!(
+                "Not scheduling the daily pipeline for {}: session {} is {}",
+                date, blocked.id, blocked.status
+            );
+            return;
+        }
+        let sessions: Vec<String> = sessions.into_iter().map(|t| t.id).collect();
+        let build_novel = DailyPipelineUseCase::task_id(TASK_TYPE_BUILD_NOVEL, &date);
+        for downstream in DailyPipelineUseCase::plan(&date, sessions.clone()) {
+            let (id, task_type) = (downstream.id.clone(), downstream.task_type.clone());
+            if self.repository.add_task(downstream).await {
+                info!("Queued {} ({}) for {}", id, task_type, date);
+            } else if id == build_novel {
+                self.replan(&id, &sessions).await;
+            }
+        }
+    }
+    async fn replan(&self, id: &str, sessions: &[String]) {
+        let Some(existing) = self.repository.get(id).await else {
+            return;
+        };
+        let missing: Vec<&String> = sessions
+            .iter()
+            .filter(|s| !existing.depends_on.contains(s))
+            .collect();
+        if missing.is_empty() {
+            return;
+        }
+        if self.repository.update_dependencies(id, sessions).await {
+            info!(
+                "Task {} now waits for {} session(s) including {:?}",
+                id,
+                sessions.len(),
+                missing
+            );
+        } else {
+            warn!(
+                "Task {} is already {}; sessions {:?} are not included",
+                id, existing.status, missing
+            );
+        }
+    }
+    async fn fail(&self, task: &Task, error: &anyhow::Error, worker_id: Option<&str>) -> bool {
+        let message = error.to_string();
+        let llm_error = error.downcast_ref::<LlmError>();
+        let retry_at = match llm_error {
+            Some(e) if !e.is_retryable() => None,
+            _ => self.retry_policy.next_attempt_at(task.attempts).map(|at| {
+                match llm_error.and_then(LlmError::retry_after_secs) {
+                    Some(secs) => {
+                        at.max(chrono::Utc::now() + chrono::Duration::seconds(secs as i64))
                     }
+                    None => at,
                 }
+            }),
+        };
+        let recorded = match worker_id {
+            Some(worker_id) => {
+                self.repository
+                    .record_failure(&task.id, worker_id, &message, retry_at)
+                    .await
+            }
+            None => self
+                .repository
+                .reclaim(&task.id, &message, retry_at)
+                .await
+                .is_some(),
+        };
+        if !recorded {
+            if let Some(worker_id) = worker_id {
+                warn!(
+                    "Task {} is no longer leased by {}; failure discarded: {}",
+                    task.id, worker_id, message
+                );
+            }
+            return false;
+        }
+        match retry_at {
+            Some(at) => warn!(
+                "Task {} failed (attempt {}/{}): {}; retrying at {}",
+                task.id, task.attempts, self.retry_policy.max_attempts, message, at
+            ),
+            None if llm_error.is_some_and(|e| !e.is_retryable()) => error!(
+                "Task {} failed permanently on a non-retryable error: {}",
+                task.id, message
+            ),
+            None => error!(
+                "Task {} failed permanently after {} attempt(s): {}",
+                task.id, task.attempts, message
+            ),
+        }
+        if retry_at.is_none() {
+            let reason = format!("dependency {} failed: {}", task.id, message);
+            for dependent in self
+                .repository
+                .cascade(&task.id, STATUS_PENDING, STATUS_FAILED, Some(&reason))
+                .await
+            {
+                warn!(
+                    "Task {} ({}) failed because {} failed",
+                    dependent.id, dependent.task_type, task.id
+                );
             }
-            sleep(Duration::from_secs(TASK_LOOP_INTERVAL_SECS)).await;
         }
+        true
     }
 }
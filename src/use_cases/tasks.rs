@@ -0,0 +1,169 @@
+use crate::domain::constants::{
+    DAILY_TASK_TYPES, STATUS_CANCELLED, STATUS_COMPLETED, STATUS_FAILED, STATUS_PENDING,
+    STATUS_PROCESSING, TASK_TYPES,
+};
+use crate::domain::{Task, TaskRepository};
+use crate::use_cases::daily_pipeline::DailyPipelineUseCase;
+use chrono::{Duration, NaiveDate, Utc};
+use std::path::Path;
+use std::sync::Arc;
+use tracing::info;
+#[derive(Debug, Default)]
+pub struct TaskFilter {
+    pub status: Option<String>,
+    pub task_type: Option<String>,
+    pub date: Option<NaiveDate>,
+}
+impl TaskFilter {
+    fn matches(&self, task: &Task) -> bool {
+        self.status.as_ref().is_none_or(|s| *s == task.status)
+            && self.task_type.as_ref().is_none_or(|t| *t == task.task_type)
+            && self
+                .date
+                .is_none_or(|d| Self::task_date(task) == d.format("%Y%m%d").to_string())
+    }
+    fn task_date(task: &Task) -> String {
+        task.date
+            .clone()
+            .or_else(|| DailyPipelineUseCase::session_date(task))
+            .unwrap_or_else(|| {
+                task.created_at
+                    .with_timezone(&chrono::Local)
+                    .format("%Y%m%d")
+                    .to_string()
+            })
+    }
+}
+pub struct TaskQueueUseCase {
+    repository: Arc<dyn TaskRepository>,
+}
+impl TaskQueueUseCase {
+    pub fn new(repository: Arc<dyn TaskRepository>) -> Self {
+        Self { repository }
+    }
+    pub async fn list(&self, filter: &TaskFilter) -> Vec<Task> {
+        self.repository
+            .load()
+            .await
+            .into_iter()
+            .filter(|t| filter.matches(t))
+            .collect()
+    }
+    pub async fn show(&self, id: &str) -> anyhow::Result<Task> {
+        self.repository
+            .get(id)
+            .await
+            .ok_or_else(|| anyhow::anyhow!("Task {} not found", id))
+    }
+    pub async fn retry(&self, id: &str) -> anyhow::Result<Task> {
+        let task = self.show(id).await?;
+        if task.status == STATUS_PROCESSING {
+            anyhow::bail!("Task {} is being processed by {:?}", id, task.lease_owner);
+        }
+        self.repository.requeue(id).await;
+        info!(
+            "Task {} ({}) requeued from {}",
+            id, task.task_type, task.status
+        );
+        if task.status == STATUS_FAILED || task.status == STATUS_CANCELLED {
+            for dependent in self
+                .repository
+                .cascade(id, &task.status, STATUS_PENDING, None)
+                .await
+            {
+                info!(
+                    "Task {} ({}) requeued with its dependency {}",
+                    dependent.id, dependent.task_type, id
+                );
+            }
+        }
+        self.show(id).await
+    }
+    pub async fn retry_failed(&self) -> Vec<Task> {
+        let mut requeued = Vec::new();
+        for task in self.repository.load().await {
+            if task.status == STATUS_FAILED && self.repository.requeue(&task.id).await {
+                info!("Task {} ({}) requeued from failed", task.id, task.task_type);
+                if let Some(task) = self.repository.get(&task.id).await {
+                    requeued.push(task);
+                }
+            }
+        }
+        requeued
+    }
+    pub async fn cancel(&self, id: &str) -> anyhow::Result<Task> {
+        let task = self.show(id).await?;
+        if [STATUS_PROCESSING, STATUS_COMPLETED, STATUS_CANCELLED].contains(&task.status.as_str()) {
+            anyhow::bail!("Task {} cannot be cancelled while {}", id, task.status);
+        }
+        if !self
+            .repository
+            .update_status(id, STATUS_CANCELLED, None)
+            .await
+        {
+            anyhow::bail!("Task {} could not be cancelled", id);
+        }
+        info!("Task {} ({}) cancelled", id, task.task_type);
+        let reason = format!("dependency {} cancelled", id);
+        for dependent in self
+            .repository
+            .cascade(id, STATUS_PENDING, STATUS_CANCELLED, Some(&reason))
+            .await
+        {
+            info!(
+                "Task {} ({}) cancelled because {} was cancelled",
+                dependent.id, dependent.task_type, id
+            );
+        }
+        self.show(id).await
+    }
+    pub async fn enqueue(
+        &self,
+        task_type: &str,
+        file_paths: Vec<String>,
+        date: Option<String>,
+    ) -> anyhow::Result<Task> {
+        if !TASK_TYPES.contains(&task_type) {
+            anyhow::bail!(
+                "Unknown task type {} (expected one of: {})",
+                task_type,
+                TASK_TYPES.join(", ")
+            );
+        }
+        if DAILY_TASK_TYPES.contains(&task_type) {
+            let date = date.ok_or_else(|| anyhow::anyhow!("{} requires --date", task_type))?;
+            let task = DailyPipelineUseCase::plan(&date, Vec::new())
+                .into_iter()
+                .find(|t| t.task_type == task_type)
+                .map(|t| Task {
+                    depends_on: Vec::new(),
+                    next_attempt_at: None,
+                    ..t
+                })
+                .ok_or_else(|| anyhow::anyhow!("no daily plan entry for {}", task_type))?;
+            if !self.repository.add_task(task.clone()).await {
+                anyhow::bail!("Task {} already exists", task.id);
+            }
+            info!("Task {} ({}) enqueued manually", task.id, task.task_type);
+            return Ok(task);
+        }
+        if file_paths.is_empty() {
+            anyhow::bail!("{} requires at least one file", task_type);
+        }
+        if let Some(missing) = file_paths.iter().find(|p| !Path::new(p).exists()) {
+            anyhow::bail!("File not found: {}", missing);
+        }
+        let task = self.repository.add(task_type, file_paths).await;
+        info!("Task {} ({}) enqueued manually", task.id, task.task_type);
+        Ok(task)
+    }
+    pub async fn prune(&self, older_than: Duration) -> u64 {
+        let before = Utc::now() - older_than;
+        let removed = self.repository.prune(before).await;
+        info!(
+            "Pruned {} finished tasks created before {}",
+            removed, before
+        );
+        removed
+    }
+}
@@ -0,0 +1,210 @@
+use serde_json::{json, Value};
+use std::io::{BufRead, BufReader, Cursor, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::{Arc, Mutex};
+use vlog_rs::domain::constants::GEMINI_UPLOAD_CHUNK_BYTES;
+use vlog_rs::domain::{LlmError, LlmProvider, LlmProviderKind};
+use vlog_rs::infrastructure::llm::ProviderClient;
+#[derive(Default)]
+struct Upload {
+    received: Vec<u8>,
+    interrupted: bool,
+    chunks: usize,
+    generated: Option<Value>,
+    deleted: Vec<String>,
+}
+struct MockGemini {
+    base: String,
+    state: Arc<Mutex<Upload>>,
+}
+impl MockGemini {
+    fn start(generate_status: u16) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("bind mock server");
+        let base = format!("http://{}", listener.local_addr().expect("local addr"));
+        let state = Arc::new(Mutex::new(Upload::default()));
+        let (server_base, server_state) = (base.clone(), state.clone());
+        std::thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let (base, state) = (server_base.clone(), server_state.clone());
+                std::thread::spawn(move || handle(stream, &base, &state, generate_status));
+            }
+        });
+        Self { base, state }
+    }
+    fn provider(&self) -> LlmProvider {
+        LlmProvider {
+            name: "gemini".to_string(),
+            kind: LlmProviderKind::Gemini,
+            model: "test-model".to_string(),
+            base_url: format!("{}/v1beta", self.base),
+            api_key: Some("test-key".to_string()),
+            timeout_secs: 30,
+            requests_per_minute: None,
+            burst: 1,
+            max_retries: 0,
+            inline_max_bytes: 1024,
+            upload_base_url: format!("{}/upload/v1beta", self.base),
+        }
+    }
+}
+fn handle(stream: TcpStream, base: &str, state: &Mutex<Upload>, generate_status: u16) {
+    let mut reader = BufReader::new(stream.try_clone().expect("clone stream"));
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line).expect("request line");
+    let mut headers = Vec::new();
+    loop {
+        let mut line = String::new();
+        reader.read_line(&mut line).expect("header line");
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            headers.push((name.trim().to_ascii_lowercase(), value.trim().to_string()));
+        }
+    }
+    let header = |name: &str| {
+        headers
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, v)| v.clone())
+    };
+    let length: usize = header("content-length")
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(0);
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body).expect("request body");
+    let mut parts = request_line.split_whitespace();
+    let (method, path) = (
+        parts.next().unwrap_or_default(),
+        parts.next().unwrap_or_default(),
+    );
+    let command = header("x-goog-upload-command").unwrap_or_default();
+    let mut state = state.lock().expect("mock state");
+    let (status, extra, reply): (u16, Vec<(&str, String)>, Value) = match (method, command.as_str())
+    {
+        ("POST", "start") if path.starts_with("/upload/v1beta/files") => (
+            200,
+            vec![("x-goog-upload-url", format!("{}/upload-session", base))],
+            json!({}),
+        ),
+        ("POST", "query") => (
+            200,
+            vec![(
+                "x-goog-upload-size-received",
+                state.received.len().to_string(),
+            )],
+            json!({}),
+        ),
+        ("POST", "upload" | "upload, finalize") => {
+            let offset: usize = header("x-goog-upload-offset")
+                .and_then(|v| v.parse().ok())
+                .expect("upload offset");
+            assert!(offset <= state.received.len(), "upload skipped bytes");
+            state.received.truncate(offset);
+            state.chunks += 1;
+            if offset > 0 && !state.interrupted {
+                state.interrupted = true;
+                state.received.extend_from_slice(&body[..1000]);
+                (503, Vec::new(), json!({}))
+            } else {
+                state.received.extend_from_slice(&body);
+                let file = json!({
+                    "file": {
+                        "name": "files/upload-test",
+                        "uri": format!("{}/v1beta/files/upload-test", base),
+                        "state": "ACTIVE"
+                    }
+                });
+                (
+                    200,
+                    Vec::new(),
+                    if command == "upload" { json!({}) } else { file },
+                )
+            }
+        }
+        ("POST", _) if path.contains(":generateContent") => {
+            state.generated = serde_json::from_slice(&body).ok();
+            let reply = json!({
+                "candidates": [{ "content": { "parts": [{ "text": "uploaded transcript" }] } }],
+                "error": { "message": "rejected" }
+            });
+            (generate_status, Vec::new(), reply)
+        }
+        ("DELETE", _) => {
+            let name = path.trim_start_matches("/v1beta/");
+            state
+                .deleted
+                .push(name.split('?').next().unwrap_or_default().to_string());
+            (200, Vec::new(), json!({}))
+        }
+        _ => (404, Vec::new(), json!({ "error": { "message": path } })),
+    };
+    drop(state);
+    let reply = reply.to_string();
+    let mut response = format!(
+        "HTTP/1.1 {} Mock\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n",
+        status,
+        reply.len()
+    );
+    for (name, value) in extra {
+        response.push_str(&format!("{}: {}\r\n", name, value));
+    }
+    response.push_str("\r\n");
+    response.push_str(&reply);
+    let mut stream = stream;
+    let _ = stream.write_all(response.as_bytes());
+}
+fn audio() -> Vec<u8> {
+    (0..GEMINI_UPLOAD_CHUNK_BYTES + 5000)
+        .map(|i| (i % 251) as u8)
+        .collect()
+}
+#[tokio::test]
+async fn resumes_an_interrupted_chunk_and_deletes_the_upload() {
+    let mock = MockGemini::start(200);
+    let client = ProviderClient::new(mock.provider()).expect("client");
+    let data = audio();
+    let text = client
+        .transcribe_audio(
+            &mut Cursor::new(data.clone()),
+            data.len() as u64,
+            "audio/wav",
+            "transcribe",
+        )
+        .await
+        .expect("transcription");
+    assert_eq!(text, "uploaded transcript");
+    let state = mock.state.lock().expect("mock state");
+    assert!(state.interrupted);
+    assert_eq!(state.chunks, 3);
+    assert!(
+        state.received == data,
+        "uploaded bytes differ from the source"
+    );
+    let generated = state.generated.as_ref().expect("generateContent request");
+    assert_eq!(
+        generated["contents"][0]["parts"][0]["file_data"]["file_uri"],
+        format!("{}/v1beta/files/upload-test", mock.base)
+    );
+    assert_eq!(state.deleted, vec!["files/upload-test".to_string()]);
+}
+#[tokio::test]
+async fn deletes_the_upload_when_generation_fails() {
+    let mock = MockGemini::start(400);
+    let client = ProviderClient::new(mock.provider()).expect("client");
+    let data = audio();
+    let result = client
+        .transcribe_audio(
+            &mut Cursor::new(data.clone()),
+            data.len() as u64,
+            "audio/wav",
+            "transcribe",
+        )
+        .await;
+    assert!(matches!(result, Err(LlmError::Api { status: 400, .. })));
+    assert_eq!(
+        mock.state.lock().expect("mock state").deleted,
+        vec!["files/upload-test".to_string()]
+    );
+}
@@ -0,0 +1,81 @@
+#![cfg(target_os = "linux")]
+use std::os::unix::fs::PermissionsExt;
+use std::path::Path;
+use std::time::Duration;
+use vlog_rs::domain::constants::DEVICE_STALL_MS;
+use vlog_rs::domain::{
+    AudioFormat, AudioRecorder as _, AudioSource, CaptureBackend, DeviceEventKind, GateMode,
+    RecordingConfig,
+};
+use vlog_rs::infrastructure::audio::AudioRecorder;
+const FAKE_PACTL: &str = r#"#!/bin/sh
+case "$*" in
+    "list sink-inputs") printf 'Sink Input #42\n\tProperties:\n\t\tapplication.name = "Firefox"\n' ;;
+    "list short sources") printf '1\talsa_input.usb-mic\tmodule-alsa-card.c\tfloat32le 1ch 48000Hz\tRUNNING\n' ;;
+esac
+"#;
+const FAKE_PAREC: &str = "#!/bin/sh\nexec sleep 60\n";
+fn install(dir: &Path, name: &str, script: &str) {
+    let path = dir.join(name);
+    std::fs::write(&path, script).expect("write fake command");
+    std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755))
+        .expect("make fake command executable");
+}
+#[test]
+fn silent_app_streams_are_not_treated_as_stalled_devices() {
+    let bin = tempfile::tempdir().expect("bin dir");
+    install(bin.path(), "pactl", FAKE_PACTL);
+    install(bin.path(), "parec", FAKE_PAREC);
+    std::env::set_var(
+        "PATH",
+        format!(
+            "{}:{}",
+            bin.path().display(),
+            std::env::var("PATH").unwrap_or_default()
+        ),
+    );
+    let out = tempfile::tempdir().expect("output dir");
+    let recorder = AudioRecorder::new();
+    recorder
+        .start(
+            out.path().join("20240101_120000.wav"),
+            RecordingConfig {
+                sample_rate: 16000,
+                channels: 1,
+                format: AudioFormat::Wav,
+                sources: vec![
+                    AudioSource {
+                        name: "app".to_string(),
+                        device_name: None,
+                        application: Some("firefox".to_string()),
+                    },
+                    AudioSource {
+                        name: "mic".to_string(),
+                        device_name: Some("usb-mic".to_string()),
+                        application: None,
+                    },
+                ],
+                backend: CaptureBackend::Pulse,
+                fixture_path: None,
+                synthetic_pattern: String::new(),
+                silence_threshold: 0.01,
+                vad_hangover_ms: 500,
+                vad_pre_roll_ms: 200,
+                vad_mode: GateMode::Drop,
+                max_segment_secs: 3600,
+                pre_roll_secs: 0,
+                session_id: None,
+            },
+        )
+        .expect("start recording");
+    std::thread::sleep(Duration::from_millis(DEVICE_STALL_MS * 2));
+    recorder.stop();
+    let lost: Vec<String> = recorder
+        .take_device_events()
+        .into_iter()
+        .filter(|e| e.kind == DeviceEventKind::Lost)
+        .map(|e| e.source)
+        .collect();
+    assert!(lost.contains(&"mic".to_string()), "lost: {:?}", lost);
+    assert!(!lost.contains(&"app".to_string()), "lost: {:?}", lost);
+}
@@ -0,0 +1,130 @@
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use vlog_rs::domain::{
+    AudioFormat, AudioRecorder as _, AudioSource, CaptureBackend, GateMode, RecordingConfig,
+};
+use vlog_rs::infrastructure::audio::{AudioRecorder, SegmentSidecar};
+fn config(backend: CaptureBackend, format: AudioFormat, vad_mode: GateMode) -> RecordingConfig {
+    RecordingConfig {
+        sample_rate: 16000,
+        channels: 1,
+        format,
+        sources: vec![AudioSource {
+            name: "mic".to_string(),
+            device_name: None,
+            application: None,
+        }],
+        backend,
+        fixture_path: None,
+        synthetic_pattern: "tone:1,silence:2".to_string(),
+        silence_threshold: 0.01,
+        vad_hangover_ms: 200,
+        vad_pre_roll_ms: 100,
+        vad_mode,
+        max_segment_secs: 3600,
+        pre_roll_secs: 0,
+        session_id: Some("session-under-test".to_string()),
+    }
+}
+fn record(dir: &Path, config: RecordingConfig, secs: u64) -> Vec<PathBuf> {
+    let recorder = AudioRecorder::new();
+    recorder
+        .start(dir.join("20240101_120000.wav"), config)
+        .expect("start recording");
+    std::thread::sleep(Duration::from_secs(secs));
+    let last = recorder.stop().expect("last segment");
+    let mut segments = recorder.take_finished_segments();
+    segments.push(last);
+    segments
+}
+fn leftovers(dir: &Path) -> Vec<PathBuf> {
+    std::fs::read_dir(dir)
+        .expect("read output dir")
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.to_string_lossy().ends_with(".part"))
+        .collect()
+}
+#[test]
+fn synthetic_recording_rotates_parts_and_drops_silence() {
+    let out = tempfile::tempdir().expect("output dir");
+    let mut config = config(CaptureBackend::Synthetic, AudioFormat::Wav, GateMode::Drop);
+    config.max_segment_secs = 1;
+    let secs = 6;
+    let segments = record(out.path(), config, secs);
+    assert!(segments.len() >= 2, "segments: {:?}", segments);
+    assert_eq!(segments[0], out.path().join("20240101_120000.wav"));
+    assert_eq!(segments[1], out.path().join("20240101_120000_002.wav"));
+    assert!(leftovers(out.path()).is_empty());
+    let mut previous_end = 0;
+    let mut file_ms = 0;
+    for (index, path) in segments.iter().enumerate() {
+        let sidecar = SegmentSidecar::load(path).expect("segment sidecar");
+        assert_eq!(sidecar.part as usize, index + 1);
+        assert_eq!(sidecar.session_id.as_deref(), Some("session-under-test"));
+        assert_eq!(sidecar.mode, GateMode::Drop);
+        assert!(!sidecar.segments.is_empty(), "{:?}", path);
+        assert!(sidecar.source_start_ms >= previous_end);
+        assert!(sidecar.file_duration_ms <= sidecar.source_end_ms - sidecar.source_start_ms);
+        previous_end = sidecar.source_end_ms;
+        file_ms += sidecar.file_duration_ms;
+        let reader = hound::WavReader::open(path).expect("committed wav");
+        assert_eq!(reader.spec().sample_rate, 16000);
+        assert_eq!(
+            reader.duration() as u64 * 1000 / 16000,
+            sidecar.file_duration_ms
+        );
+    }
+    assert!(file_ms < previous_end);
+    assert!(file_ms * 2 < secs * 1000, "kept {}ms of {}s", file_ms, secs);
+}
+#[test]
+fn fixture_recording_resamples_into_flac_and_marks_speech() {
+    let fixtures = tempfile::tempdir().expect("fixture dir");
+    let fixture = fixtures.path().join("tone.wav");
+    let mut writer = hound::WavWriter::create(
+        &fixture,
+        hound::WavSpec {
+            channels: 1,
+            sample_rate: 48000,
+            bits_per_sample: 16,
+            sample_format: hound::SampleFormat::Int,
+        },
+    )
+    .expect("create fixture");
+    for i in 0..48000 * 2 {
+        let speaking = i < 48000;
+        let value = if speaking {
+            ((i as f32 * 440.0 * std::f32::consts::TAU / 48000.0).sin() * 8000.0) as i16
+        } else {
+            0
+        };
+        writer.write_sample(value).expect("write fixture");
+    }
+    writer.finalize().expect("finalize fixture");
+    let out = tempfile::tempdir().expect("output dir");
+    let mut config = config(CaptureBackend::File, AudioFormat::Flac, GateMode::Mark);
+    config.fixture_path = Some(fixture);
+    let segments = record(out.path(), config, 3);
+    assert_eq!(segments, vec![out.path().join("20240101_120000.flac")]);
+    assert!(leftovers(out.path()).is_empty());
+    let sidecar = SegmentSidecar::load(&segments[0]).expect("segment sidecar");
+    assert_eq!(sidecar.mode, GateMode::Mark);
+    assert_eq!(
+        sidecar.file_duration_ms,
+        sidecar.source_end_ms - sidecar.source_start_ms
+    );
+    assert!(!sidecar.segments.is_empty());
+    let speech_ms: u64 = sidecar
+        .segments
+        .iter()
+        .map(|s| s.file_end_ms - s.file_start_ms)
+        .sum();
+    assert!(speech_ms < sidecar.file_duration_ms);
+    let mut reader = claxon::FlacReader::open(&segments[0]).expect("committed flac");
+    let info = reader.streaminfo();
+    assert_eq!(info.sample_rate, 16000);
+    let samples = reader.samples().count() as u64;
+    assert_eq!(Some(samples), info.samples);
+    assert_eq!(samples * 1000 / 16000, sidecar.file_duration_ms);
+}